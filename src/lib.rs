@@ -1 +1,17 @@
+// the code base favours explicit returns and hand written Default impls
+#![allow(
+    clippy::needless_return,
+    clippy::needless_bool,
+    clippy::derivable_impls,
+    clippy::field_reassign_with_default,
+    clippy::new_without_default,
+    clippy::result_large_err,
+    clippy::single_char_add_str,
+    clippy::unused_unit,
+    clippy::inherent_to_string_shadow_display,
+    clippy::module_inception,
+    clippy::len_zero,
+    clippy::legacy_numeric_constants,
+    clippy::to_string_in_format_args,
+)]
 pub mod token;
@@ -1,7 +1,137 @@
-use anyhow::Result;
+#![allow(clippy::needless_return)]
+use std::io::Read;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Duration;
 
-fn main() -> Result<()> {
-    let _ = interpreter::token::repl::start();
-    
-    Ok(())
+use interpreter::token::evaluator::{Evalulator, DEFAULT_MAX_CALL_DEPTH};
+use interpreter::token::object::{Object, ObjectType};
+use interpreter::token::script;
+
+const USAGE: &str = "usage: interpreter [options] [script.mk | - | -e <expression>]
+  no arguments       start the REPL
+  script.mk          run a script file
+  -                  run a script read from stdin
+  -e <expression>    evaluate a single expression and print the result
+
+options:
+  --max-depth <n>    fail with a stack overflow past n nested calls (default 1000)
+  --max-steps <n>    stop a program after it evaluates n nodes
+  --timeout <ms>     stop a program after it runs for ms milliseconds";
+
+// settings given before the script arguments
+struct Options {
+    max_call_depth: usize,
+    step_limit: Option<u64>,
+    time_limit: Option<Duration>,
+}
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            step_limit: None,
+            time_limit: None,
+        }
+    }
+}
+impl Options {
+    fn evalulator(&self) -> Evalulator {
+        let mut evalulator = Evalulator::new();
+        evalulator.max_call_depth = self.max_call_depth;
+        evalulator.step_limit = self.step_limit;
+        evalulator.time_limit = self.time_limit;
+        return evalulator;
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_options(&mut args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            return ExitCode::from(2);
+        },
+    };
+    // the evaluator sizes its own stack for the call depth allowed
+    return run(options, args);
+}
+
+fn parse_options(args: &mut Vec<String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    while let Some(flag) = args.first() {
+        match flag.as_str() {
+            "--max-depth" => options.max_call_depth = option_value(args)?,
+            "--max-steps" => options.step_limit = Some(option_value(args)?),
+            "--timeout" => options.time_limit = Some(Duration::from_millis(option_value(args)?)),
+            _ => break,
+        }
+        args.drain(..2);
+    }
+    return Ok(options);
+}
+
+// the number following the flag at the front of args
+fn option_value<T: FromStr>(args: &[String]) -> Result<T, String> {
+    let flag = &args[0];
+    let value = args.get(1).ok_or(format!("{} needs a number", flag))?;
+    return value.parse().map_err(|_| format!("{} needs a number, got {}", flag, value));
+}
+
+fn run(options: Options, args: Vec<String>) -> ExitCode {
+    match args.as_slice() {
+        [] => {
+            if let Err(error) = interpreter::token::repl::start(options.evalulator()) {
+                eprintln!("{}", error);
+                return ExitCode::FAILURE;
+            }
+            return ExitCode::SUCCESS;
+        },
+        [flag] if flag == "-h" || flag == "--help" => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        },
+        [flag, source] if flag == "-e" => {
+            let mut evalulator = options.evalulator();
+            return report(script::run(&mut evalulator, source.clone()), true);
+        },
+        [path] if path == "-" => {
+            let mut source = String::new();
+            if let Err(error) = std::io::stdin().read_to_string(&mut source) {
+                eprintln!("could not read stdin: {}", error);
+                return ExitCode::FAILURE;
+            }
+            let mut evalulator = options.evalulator();
+            return report(script::run(&mut evalulator, source), false);
+        },
+        [path] if !path.starts_with('-') => {
+            let mut evalulator = options.evalulator();
+            match script::run_file(&mut evalulator, path) {
+                Ok(result) => return report(result, false),
+                Err(error) => {
+                    eprintln!("could not read {}: {}", path, error);
+                    return ExitCode::FAILURE;
+                },
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        },
+    }
+}
+
+fn report(result: Result<Object, script::RunError>, print_result: bool) -> ExitCode {
+    match result {
+        Ok(object) => {
+            if print_result && object != Object::Null {
+                println!("{}", object.inspect());
+            }
+            return ExitCode::SUCCESS;
+        },
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        },
+    }
 }
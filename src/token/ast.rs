@@ -1,5 +1,7 @@
 use std::fmt::Display;
 
+use super::token::Span;
+
 pub enum Node {
     Program(Program),
     Statement(Statement),
@@ -42,11 +44,20 @@ impl Statement {
         };
         return expression;
     }
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::BlockStatement(block) => block.span,
+            Statement::LetStatement(let_) => let_.span,
+            Statement::ReturnStatement(return_) => return_.span,
+            Statement::Expression(expression) => expression.span(),
+        }
+    }
 }
 #[derive(Debug,Clone,PartialEq)]
 pub struct LetStatement {
     pub name: String,
     pub value: Expression,
+    pub span: Span,
 }
 impl Display for LetStatement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -57,6 +68,7 @@ impl Display for LetStatement {
 #[derive(Debug,Clone,PartialEq)]
 pub struct ReturnStatement {
     pub ret_value: Expression,
+    pub span: Span,
 }
 impl Display for ReturnStatement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -67,25 +79,54 @@ impl Display for ReturnStatement {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     CallExpression(CallExpression),
-    Ident(String),
-    Int(String),
-    Literal(Literal),
+    Ident(String, Span),
+    Int(String, Span),
+    Literal(Literal, Span),
     PrefixExpression(PrefixExpression),
     InfixExpression(InfixExpression),
     IfExpression(IfExpression),
     FunctionLiteral(FunctionLiteral),
+    ArrayLiteral(ArrayLiteral),
+    IndexExpression(IndexExpression),
+    SliceExpression(SliceExpression),
+    HashLiteral(HashLiteral),
+    InterpolatedString(InterpolatedString),
 }
 impl Display for Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Expression::CallExpression(call) => call.fmt(f),
             Expression::FunctionLiteral(function) => function.fmt(f),
-            Expression::Ident(string) => string.fmt(f),
+            Expression::Ident(string, _) => string.fmt(f),
             Expression::IfExpression(if_) => if_.fmt(f),
-            Expression::Int(num) => write!(f, "{num}"),
-            Expression::Literal(literal) => literal.fmt(f),
+            Expression::Int(num, _) => write!(f, "{num}"),
+            Expression::Literal(literal, _) => literal.fmt(f),
             Expression::PrefixExpression(prefix) => prefix.fmt(f),
             Expression::InfixExpression(infix) => infix.fmt(f),
+            Expression::ArrayLiteral(array) => array.fmt(f),
+            Expression::IndexExpression(index) => index.fmt(f),
+            Expression::SliceExpression(slice) => slice.fmt(f),
+            Expression::HashLiteral(hash) => hash.fmt(f),
+            Expression::InterpolatedString(string) => string.fmt(f),
+        }
+    }
+}
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::CallExpression(call) => call.span,
+            Expression::FunctionLiteral(function) => function.span,
+            Expression::Ident(_, span) => *span,
+            Expression::IfExpression(if_) => if_.span,
+            Expression::Int(_, span) => *span,
+            Expression::Literal(_, span) => *span,
+            Expression::PrefixExpression(prefix) => prefix.span,
+            Expression::InfixExpression(infix) => infix.span,
+            Expression::ArrayLiteral(array) => array.span,
+            Expression::IndexExpression(index) => index.span,
+            Expression::SliceExpression(slice) => slice.span,
+            Expression::HashLiteral(hash) => hash.span,
+            Expression::InterpolatedString(string) => string.span,
         }
     }
 }
@@ -93,6 +134,7 @@ impl Display for Expression {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     Int(String),
+    Float(String),
     String(String),
     Bool(bool),
 }
@@ -100,6 +142,7 @@ impl Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Literal::Int(value) => write!(f, "{value}"),
+            Literal::Float(value) => write!(f, "{value}"),
             Literal::String(string) => write!(f, "{string}"),
             Literal::Bool(bool) => write!(f, "{bool}"),
         }
@@ -110,6 +153,7 @@ pub struct PrefixExpression {
     pub prefix_op: PrefixOp,
     // use a box on right expression to prevent infinite recursion of Expressions
     pub expression: Box<Expression>,
+    pub span: Span,
 }
 impl Display for PrefixExpression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -135,6 +179,7 @@ pub struct InfixExpression {
     pub left_expression: Box<Expression>,
     pub infix_op: InfixOp,
     pub right_expression: Box<Expression>,
+    pub span: Span,
 }
 impl Display for InfixExpression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -151,6 +196,10 @@ impl Display for InfixOp {
             InfixOp::DIVIDE => write!(f, "/"),
             InfixOp::GREATERTHAN => write!(f, ">"),
             InfixOp::LESSTHAN => write!(f, "<"),
+            InfixOp::GREATEREQUAL => write!(f, ">="),
+            InfixOp::LESSEQUAL => write!(f, "<="),
+            InfixOp::AND => write!(f, "&&"),
+            InfixOp::OR => write!(f, "||"),
             InfixOp::PLUS => write!(f, "+"),
             InfixOp::MULTIPLY => write!(f, "*"),
             InfixOp::SUBTRACT => write!(f, "-"),
@@ -165,16 +214,96 @@ pub enum InfixOp {
     DIVIDE,
     GREATERTHAN,
     LESSTHAN,
+    GREATEREQUAL,
+    LESSEQUAL,
+    AND,
+    OR,
     PLUS,
     MULTIPLY,
     SUBTRACT,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrayLiteral {
+    pub elements: Vec<Expression>,
+    pub span: Span,
+}
+impl Display for ArrayLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let elements: Vec<String> = self.elements.iter().map(|entry| entry.to_string()).collect();
+        write!(f, "[{}]", elements.join(", "))
+    }
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexExpression {
+    pub left: Box<Expression>,
+    pub index: Box<Expression>,
+    pub span: Span,
+}
+impl Display for IndexExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}[{}])", self.left, self.index)
+    }
+}
+// "Hello ${name}!" is a literal, an expression and another literal
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterpolatedString {
+    pub parts: Vec<InterpolationPart>,
+    pub span: Span,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpolationPart {
+    Literal(String, Span),
+    Expression(Expression),
+}
+impl Display for InterpolatedString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for part in &self.parts {
+            match part {
+                InterpolationPart::Literal(string, _) => write!(f, "{}", string)?,
+                InterpolationPart::Expression(expression) => write!(f, "${{{}}}", expression)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// left[start:end], a missing bound runs to that end of left
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliceExpression {
+    pub left: Box<Expression>,
+    pub start: Option<Box<Expression>>,
+    pub end: Option<Box<Expression>>,
+    pub span: Span,
+}
+impl Display for SliceExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bound = |bound: &Option<Box<Expression>>| bound.as_ref().map(|bound| bound.to_string()).unwrap_or_default();
+        write!(f, "({}[{}:{}])", self.left, bound(&self.start), bound(&self.end))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HashLiteral {
+    // kept as a list of pairs, keys are only hashed once they are evaluated
+    pub pairs: Vec<(Expression, Expression)>,
+    pub span: Span,
+}
+impl Display for HashLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pairs: Vec<String> = self.pairs.iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect();
+        write!(f, "{{{}}}", pairs.join(", "))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct IfExpression {
     pub condition: Box<Expression>,
     pub consequence: BlockStatement,
     pub alternative: Option<BlockStatement>,
+    pub span: Span,
 }
 impl Display for IfExpression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -189,6 +318,7 @@ impl Display for IfExpression {
 pub struct FunctionLiteral {
     pub parameters: Parameters,
     pub body: BlockStatement,
+    pub span: Span,
 }
 impl Display for FunctionLiteral {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -201,6 +331,7 @@ impl Default for FunctionLiteral {
         FunctionLiteral {
             parameters: Parameters::default(),
             body: BlockStatement::default(),
+            span: Span::default(),
         }
     }
 }
@@ -273,6 +404,7 @@ impl Arguments {
 pub struct CallExpression {
     pub function: Box<Expression>,
     pub arguments: Arguments,
+    pub span: Span,
 }
 impl Display for CallExpression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -313,6 +445,117 @@ impl Program {
     pub fn new() -> Program {
        Program::default() 
     }
+    // indented tree of the program, one node per line with where it starts
+    pub fn pretty(&self) -> String {
+        let mut output = String::new();
+        for entry in &self.statements {
+            entry.pretty(0, &mut output);
+        }
+        return output;
+    }
+}
+fn pretty_line(indent: usize, text: &str, span: Span, output: &mut String) {
+    output.push_str(&format!("{}{} @ {}\n", "  ".repeat(indent), text, span.start));
+}
+impl Statement {
+    fn pretty(&self, indent: usize, output: &mut String) {
+        match self {
+            Statement::LetStatement(let_) => {
+                pretty_line(indent, &format!("Let {}", let_.name), let_.span, output);
+                let_.value.pretty(indent + 1, output);
+            },
+            Statement::ReturnStatement(return_) => {
+                pretty_line(indent, "Return", return_.span, output);
+                return_.ret_value.pretty(indent + 1, output);
+            },
+            Statement::BlockStatement(block) => block.pretty(indent, output),
+            Statement::Expression(expression) => expression.pretty(indent, output),
+        }
+    }
+}
+impl Expression {
+    fn pretty(&self, indent: usize, output: &mut String) {
+        match self {
+            Expression::Ident(name, span) => pretty_line(indent, &format!("Ident {}", name), *span, output),
+            Expression::Int(num, span) => pretty_line(indent, &format!("Int {}", num), *span, output),
+            Expression::Literal(literal, span) => {
+                let text = match literal {
+                    Literal::Int(value) => format!("Int {}", value),
+                    Literal::Float(value) => format!("Float {}", value),
+                    Literal::String(string) => format!("String {:?}", string),
+                    Literal::Bool(bool) => format!("Bool {}", bool),
+                };
+                pretty_line(indent, &text, *span, output);
+            },
+            Expression::PrefixExpression(prefix) => {
+                pretty_line(indent, &format!("Prefix {}", prefix.prefix_op), prefix.span, output);
+                prefix.expression.pretty(indent + 1, output);
+            },
+            Expression::InfixExpression(infix) => {
+                pretty_line(indent, &format!("Infix {}", infix.infix_op), infix.span, output);
+                infix.left_expression.pretty(indent + 1, output);
+                infix.right_expression.pretty(indent + 1, output);
+            },
+            Expression::IfExpression(if_) => {
+                pretty_line(indent, "If", if_.span, output);
+                if_.condition.pretty(indent + 1, output);
+                if_.consequence.pretty(indent + 1, output);
+                if let Some(alt) = &if_.alternative {
+                    alt.pretty(indent + 1, output);
+                }
+            },
+            Expression::FunctionLiteral(function) => {
+                pretty_line(indent, &format!("Function ({})", function.parameters), function.span, output);
+                function.body.pretty(indent + 1, output);
+            },
+            Expression::CallExpression(call) => {
+                pretty_line(indent, "Call", call.span, output);
+                call.function.pretty(indent + 1, output);
+                for entry in &call.arguments.variables {
+                    entry.pretty(indent + 1, output);
+                }
+            },
+            Expression::ArrayLiteral(array) => {
+                pretty_line(indent, "Array", array.span, output);
+                for entry in &array.elements {
+                    entry.pretty(indent + 1, output);
+                }
+            },
+            Expression::IndexExpression(index) => {
+                pretty_line(indent, "Index", index.span, output);
+                index.left.pretty(indent + 1, output);
+                index.index.pretty(indent + 1, output);
+            },
+            Expression::SliceExpression(slice) => {
+                pretty_line(indent, "Slice", slice.span, output);
+                slice.left.pretty(indent + 1, output);
+                // label the bounds so [a:] and [:a] look different
+                for (label, bound) in [("From", &slice.start), ("To", &slice.end)] {
+                    if let Some(bound) = bound {
+                        pretty_line(indent + 1, label, bound.span(), output);
+                        bound.pretty(indent + 2, output);
+                    }
+                }
+            },
+            Expression::InterpolatedString(string) => {
+                pretty_line(indent, "Interpolation", string.span, output);
+                for part in &string.parts {
+                    match part {
+                        InterpolationPart::Literal(string, span) => pretty_line(indent + 1, &format!("String {:?}", string), *span, output),
+                        InterpolationPart::Expression(expression) => expression.pretty(indent + 1, output),
+                    }
+                }
+            },
+            Expression::HashLiteral(hash) => {
+                pretty_line(indent, "Hash", hash.span, output);
+                for (key, value) in &hash.pairs {
+                    pretty_line(indent + 1, "Pair", key.span().to(value.span()), output);
+                    key.pretty(indent + 2, output);
+                    value.pretty(indent + 2, output);
+                }
+            },
+        }
+    }
 }
 impl Display for Program {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -325,11 +568,13 @@ impl Display for Program {
 #[derive(Debug,Clone,PartialEq)]
 pub struct BlockStatement {
     pub statements: Vec<Statement>,
+    pub span: Span,
 }
 impl Default for BlockStatement {
     fn default() -> Self {
         BlockStatement {
-            statements: Vec::<Statement>::new()
+            statements: Vec::<Statement>::new(),
+            span: Span::default(),
         }
     }
 }
@@ -346,4 +591,10 @@ impl BlockStatement {
     fn get_expression(&self) -> String {
         return self.statements[0].get_expression();
     }
+    fn pretty(&self, indent: usize, output: &mut String) {
+        pretty_line(indent, "Block", self.span, output);
+        for entry in &self.statements {
+            entry.pretty(indent + 1, output);
+        }
+    }
 }
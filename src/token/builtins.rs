@@ -0,0 +1,94 @@
+use crate::token::evaluator::EvalError;
+use crate::token::object::{Object, Integer, Array, Builtin, BuiltinFunction, ObjectType};
+
+// every builtin the evaluator knows about, looked up by name when an
+// identifier is not bound in the environment
+const BUILTINS: [(&str, BuiltinFunction); 6] = [
+    ("len", len),
+    ("puts", puts),
+    ("first", first),
+    ("last", last),
+    ("rest", rest),
+    ("push", push),
+];
+
+pub fn get_builtin(name: &str) -> Option<Object> {
+    for (builtin_name, function) in BUILTINS {
+        if builtin_name == name {
+            return Some(Object::Builtin(Builtin::new(builtin_name, function)));
+        }
+    }
+    return None;
+}
+
+pub fn builtin_names() -> Vec<String> {
+    return BUILTINS.iter().map(|(name, _)| name.to_string()).collect();
+}
+
+fn check_arity(name: &str, args: &[Object], expected: usize) -> Result<(), EvalError> {
+    if args.len() != expected {
+        return Err(EvalError::WrongArgumentCount(name.to_string(), expected, args.len()));
+    }
+    Ok(())
+}
+
+fn len(args: Vec<Object>) -> Result<Object, EvalError> {
+    // len("hello") = 5, len("héllo") = 5 too, strings count chars not bytes
+    check_arity("len", &args, 1)?;
+    match &args[0] {
+        Object::String(string) => return Ok(Object::Integer(Integer::from_num(string.value.chars().count() as i64))),
+        Object::Array(array) => return Ok(Object::Integer(Integer::from_num(array.elements.len() as i64))),
+        other => return Err(EvalError::WrongArgumentType("len".to_string(), other.clone())),
+    }
+}
+
+fn puts(args: Vec<Object>) -> Result<Object, EvalError> {
+    // print every argument on its own line
+    for entry in args {
+        println!("{}", entry.inspect());
+    }
+    return Ok(Object::Null);
+}
+
+fn first(args: Vec<Object>) -> Result<Object, EvalError> {
+    check_arity("first", &args, 1)?;
+    match &args[0] {
+        Object::Array(array) => return Ok(array.elements.first().cloned().unwrap_or(Object::Null)),
+        other => return Err(EvalError::WrongArgumentType("first".to_string(), other.clone())),
+    }
+}
+
+fn last(args: Vec<Object>) -> Result<Object, EvalError> {
+    check_arity("last", &args, 1)?;
+    match &args[0] {
+        Object::Array(array) => return Ok(array.elements.last().cloned().unwrap_or(Object::Null)),
+        other => return Err(EvalError::WrongArgumentType("last".to_string(), other.clone())),
+    }
+}
+
+fn rest(args: Vec<Object>) -> Result<Object, EvalError> {
+    // everything but the first element as a new array, Null for an empty array
+    check_arity("rest", &args, 1)?;
+    match &args[0] {
+        Object::Array(array) => {
+            if array.elements.is_empty() {
+                return Ok(Object::Null);
+            }
+            return Ok(Object::Array(Array::new(array.elements[1..].to_vec())));
+        },
+        other => return Err(EvalError::WrongArgumentType("rest".to_string(), other.clone())),
+    }
+}
+
+fn push(args: Vec<Object>) -> Result<Object, EvalError> {
+    // arrays are never changed in place, push returns a new array
+    check_arity("push", &args, 2)?;
+    match &args[0] {
+        Object::Array(array) => {
+            let mut elements = array.elements.clone();
+            elements.push(args[1].clone());
+            return Ok(Object::Array(Array::new(elements)));
+        },
+        other => return Err(EvalError::WrongArgumentType("push".to_string(), other.clone())),
+    }
+}
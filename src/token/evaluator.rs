@@ -1,10 +1,13 @@
 use std::fmt::Display;
-use crate::token::object::{Object, Integer, Boolean, Return, Environment};
-use crate::token::ast::{Node,Expression,Literal, Program, PrefixOp, Arguments};
-use super::ast::{Statement, InfixOp, BlockStatement, IfExpression, ReturnStatement, LetStatement, FunctionLiteral, CallExpression};
-use super::object::{Function, ObjectType, StringObject};
+use crate::token::object::{Object, Integer, Float, Boolean, Return, Environment};
+use crate::token::ast::{Node,Expression,Literal, Program, PrefixOp};
+use super::ast::{Statement, InfixOp, BlockStatement, IfExpression, ReturnStatement, LetStatement, FunctionLiteral, CallExpression, IndexExpression, SliceExpression, HashLiteral};
+use super::ast::{PrefixExpression, InfixExpression, ArrayLiteral, InterpolatedString, InterpolationPart};
+use super::object::{Function, ObjectType, StringObject, Array, Hash, HashPair};
+use super::builtins;
 use std::cell::RefCell;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, PartialEq)]
 pub enum EvalError{
@@ -15,6 +18,25 @@ pub enum EvalError{
     UnknownInfix(Object, InfixOp, Object),
     UnknownIdent(String),
     FailedObject(Object),
+    UnsupportedIndex(Object, Object),
+    UnusableHashKey(Object),
+    // name, expected, got
+    WrongArgumentCount(String, usize, usize),
+    WrongArgumentType(String, Object),
+    // the call depth limit that was hit
+    StackOverflow(usize),
+    // native stack in bytes that ran out before the call depth limit was reached
+    StackExhausted(usize),
+    // steps used when the step limit ran out
+    OutOfSteps(u64),
+    // time spent when the time limit ran out
+    Timeout(Duration),
+    // the operation that did not fit in an integer
+    Overflow(String),
+    // the left hand side of the division
+    DivisionByZero(Object),
+    // bytes a string would have needed, usize::MAX when that does not fit in one
+    StringTooLong(usize),
 }
 impl Display for EvalError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -26,26 +48,106 @@ impl Display for EvalError {
             EvalError::UnknownIdent(string) => write!(f, "Unknown Identifier: {}", string),
             EvalError::FailedObject(obj1) => write!(f, "Use of an unsupported object: {}", obj1.obj_type()),
             EvalError::FailedExpression(express) => write!(f, "Failed Evaluation of expression: {}", express),
+            EvalError::UnsupportedIndex(obj1, obj2) => write!(f, "Index operator not supported: {}[{}]", obj1.obj_type(), obj2.obj_type()),
+            EvalError::UnusableHashKey(obj1) => write!(f, "Unusable as hash key: {}", obj1.obj_type()),
+            EvalError::WrongArgumentCount(name, expected, got) => write!(f, "Wrong number of arguments to {}: expected {}, got {}", name, expected, got),
+            EvalError::WrongArgumentType(name, obj1) => write!(f, "Argument to {} not supported: {}", name, obj1.obj_type()),
+            EvalError::StackOverflow(limit) => write!(f, "Stack overflow: more than {} nested calls", limit),
+            EvalError::StackExhausted(limit) => write!(f, "Stack overflow: evaluation used more than {} bytes of stack", limit),
+            EvalError::OutOfSteps(used) => write!(f, "Step limit reached: evaluation stopped after {} steps", used),
+            EvalError::Timeout(spent) => write!(f, "Time limit reached: evaluation stopped after {}ms", spent.as_millis()),
+            EvalError::Overflow(operation) => write!(f, "Integer overflow: {}", operation),
+            EvalError::DivisionByZero(obj1) => write!(f, "Division by zero: {} / 0", obj1.inspect()),
+            EvalError::StringTooLong(length) => write!(f, "String too long: {} bytes", length),
         }
     }
 }
 
 
+// deep enough for ordinary recursion, shallow enough to fit in a few dozen MB of stack
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+// native stack one level of calls takes with a few levels of nested expressions around the
+// call, debug builds are the hungry ones
+// each evaluation runs on a thread of its own with (max_call_depth + 1) * STACK_PER_CALL of
+// stack, plus a MB of headroom
+pub const STACK_PER_CALL: usize = if cfg!(debug_assertions) { 256 * 1024 } else { 64 * 1024 };
+// longest string in bytes an operation may build, checked before anything is allocated
+pub const MAX_STRING_LEN: usize = 1 << 30;
+// bytes or elements an operation may copy or scan for one step, so a single operation on a
+// big string or array uses up the budgets like the nodes it would take to build it
+const WORK_PER_STEP: usize = 1024;
 
 pub struct Evalulator {
     // environment used to keep track up variable bindings
+    // using Rc so functions can share the environment they were defined in
     // using RefCell to allow interior mutatability
-    pub environment: RefCell<Environment>,
+    pub environment: Rc<RefCell<Environment>>,
+    // calls nested deeper than this are a StackOverflow error instead of a crash, the
+    // evaluation thread is sized to fit them
+    pub max_call_depth: usize,
+    call_depth: usize,
+    // where the outermost eval started on the evaluation thread's stack, None outside of eval
+    stack_base: Option<usize>,
+    // budgets for a single program, both are unlimited when None
+    // every node passed to eval is one step
+    pub step_limit: Option<u64>,
+    pub time_limit: Option<Duration>,
+    steps: u64,
+    started: Instant,
+    // every environment made so far, functions stored in their own environment keep it
+    // alive through an Rc cycle, so these are cleared on reset and drop to free them
+    environments: Vec<Weak<RefCell<Environment>>>,
+    // environments alive after the last sweep of dead entries
+    live_environments: usize,
 }
 
 impl Evalulator {
     pub fn new() -> Self {
+        let environment = Rc::new(RefCell::new(Environment::new()));
         Evalulator {
-            environment: RefCell::new(Environment::new()),
+            environments: vec![Rc::downgrade(&environment)],
+            live_environments: 1,
+            environment,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            call_depth: 0,
+            stack_base: None,
+            step_limit: None,
+            time_limit: None,
+            steps: 0,
+            started: Instant::now(),
         }
     }
+    // steps taken by the last program evaluated
+    pub fn steps(&self) -> u64 {
+        return self.steps;
+    }
+    // forget every binding but keep the limits
+    pub fn reset(&mut self) -> () {
+        self.clear_environments();
+        self.environment = self.new_environment(Environment::new());
+        self.call_depth = 0;
+    }
+    fn new_environment(&mut self, environment: Environment) -> Rc<RefCell<Environment>> {
+        let environment = Rc::new(RefCell::new(environment));
+        // most call environments are gone by the time the next call is made, so sweep
+        // the dead ones whenever the list has doubled
+        if self.environments.len() >= self.live_environments * 2 {
+            self.environments.retain(|env| env.strong_count() > 0);
+            self.live_environments = self.environments.len().max(1);
+        }
+        self.environments.push(Rc::downgrade(&environment));
+        return environment;
+    }
+    fn clear_environments(&mut self) -> () {
+        for env in self.environments.drain(..) {
+            if let Some(env) = env.upgrade() {
+                env.borrow_mut().clear();
+            }
+        }
+        self.live_environments = 1;
+    }
     pub fn print(&self) -> () {
-        self.environment.borrow_mut().print();
+        self.environment.borrow().print();
     }
     pub fn new_error(&self, input: String) -> Result<Object, EvalError> {
        return Ok(Object::Error(input)); 
@@ -53,41 +155,161 @@ impl Evalulator {
 
     pub fn eval(&mut self, node: Node) -> Result<Object, EvalError> {
         // three Node types
+        // every nested evaluation comes back through here, so the arms only hand off to
+        // helpers to keep this frame small
+        let base = match self.stack_base {
+            Some(base) => base,
+            // the outermost eval moves to a thread with room for max_call_depth calls
+            None => return self.eval_on_own_stack(node),
+        };
+        // STACK_PER_CALL is an estimate, so deep nesting inside a call is caught here before
+        // it runs into the headroom
+        if base.abs_diff(stack_address()) > self.stack_limit() {
+            return Err(EvalError::StackExhausted(self.stack_limit()));
+        }
+        if let Node::Program(_) = node {
+            // each program gets the whole budget
+            self.steps = 0;
+            self.started = Instant::now();
+        }
+        self.take_step()?;
         match node {
             Node::Program(program) => self.eval_program(program),
-            Node::Statement(statement) => match statement {
-                  Statement::LetStatement(let_) => self.eval_let_statement(let_),
-                  Statement::BlockStatement(block) => self.eval_block_statement(block),
-                  Statement::ReturnStatement(ret) => self.eval_ret_statement(ret),
-                  Statement::Expression(express) => self.eval(Node::Expression(express)),
+            Node::Statement(statement) => self.eval_statement(statement),
+            Node::Expression(express) => self.eval_expression(express),
+        }
+    }
+    // stack the evaluation may use, the top level counts as one more call
+    fn stack_limit(&self) -> usize {
+        return self.max_call_depth.saturating_add(1).saturating_mul(STACK_PER_CALL);
+    }
+    fn eval_on_own_stack(&mut self, node: Node) -> Result<Object, EvalError> {
+        let stack_size = self.stack_limit().saturating_add(1 << 20);
+        let evaluated = std::thread::scope(|scope| {
+            let job = Exclusive((&mut *self, node));
+            let spawned = std::thread::Builder::new().stack_size(stack_size).spawn_scoped(scope, move || {
+                let (evalulator, node) = job.into_inner();
+                evalulator.stack_base = Some(stack_address());
+                return Exclusive(evalulator.eval(node));
+            });
+            match spawned {
+                Ok(handle) => return handle.join().map(Exclusive::into_inner),
+                Err(error) => return Ok(Err(EvalError::FailedEval(
+                    format!("could not start evaluating with a {} byte stack: {}", stack_size, error)))),
+            }
+        });
+        // errors unwind through every call, panics too, so nothing is left nested
+        self.stack_base = None;
+        self.call_depth = 0;
+        match evaluated {
+            Ok(evaluated) => return evaluated,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+    fn eval_statement(&mut self, statement: Statement) -> Result<Object, EvalError> {
+        match statement {
+            Statement::LetStatement(let_) => self.eval_let_statement(let_),
+            Statement::BlockStatement(block) => self.eval_block_statement(block),
+            Statement::ReturnStatement(ret) => self.eval_ret_statement(ret),
+            Statement::Expression(express) => self.eval(Node::Expression(express)),
+        }
+    }
+    fn eval_expression(&mut self, express: Expression) -> Result<Object, EvalError> {
+        match express {
+            Expression::Literal(lit, _) => self.eval_literal(lit),
+            Expression::PrefixExpression(prefix) => self.eval_prefix(prefix),
+            Expression::InfixExpression(infix) => self.eval_infix(infix),
+            Expression::IfExpression(iff) => self.eval_if_expression(iff),
+            Expression::Ident(ident, _) => self.eval_ident_expression(ident),
+            Expression::FunctionLiteral(func) => self.eval_function_expression(func),
+            Expression::CallExpression(call) => self.eval_call_expression(call),
+            Expression::ArrayLiteral(array) => self.eval_array_literal(array),
+            Expression::IndexExpression(index) => self.eval_index_expression(index),
+            Expression::SliceExpression(slice) => self.eval_slice_expression(slice),
+            Expression::HashLiteral(hash) => self.eval_hash_literal(hash),
+            Expression::InterpolatedString(string) => self.eval_interpolated_string(string),
+            express => Err(EvalError::FailedExpression(express)),
+        }
+    }
+    fn eval_literal(&mut self, lit: Literal) -> Result<Object, EvalError> {
+        match lit {
+            // the parser rejects literals that do not fit, this covers hand built programs
+            Literal::Int(int) => match Integer::parse(&int) {
+                Some(int) => Ok(Object::Integer(int)),
+                None => Err(EvalError::Overflow(int)),
             },
-            Node::Expression(express) => match express {
-                Expression::Literal(lit) => match lit {
-                    Literal::Int(int) => Ok(Object::Integer(Integer::new(int))),
-                    Literal::Bool(boo) => match boo {
-                        true => Ok(Object::Boolean(Boolean::new(true))),
-                        false => Ok(Object::Boolean(Boolean::new(false))),
-                    },
-                    Literal::String(string) => {
-                        Ok(Object::String(StringObject::new(string)))
-                    },
-                },
-                Expression::PrefixExpression(prefix) => {
-                    let expression = self.eval(Node::Expression(*prefix.expression.clone()))?;
-                    self.eval_prefix_expression(prefix.prefix_op.clone(), expression)
-                },
-                Expression::InfixExpression(infix) => {
-                    let left = self.eval(Node::Expression(*infix.left_expression.clone()))?;
-                    let right = self.eval(Node::Expression(*infix.right_expression.clone()))?;
-                    self.eval_infix_expression(infix.infix_op.clone(), left, right)
+            Literal::Float(float) => match float.parse::<f64>() {
+                Ok(value) => Ok(Object::Float(Float::new(value))),
+                Err(_) => Err(EvalError::FailedEval(float)),
+            },
+            Literal::Bool(boo) => match boo {
+                true => Ok(Object::Boolean(Boolean::new(true))),
+                false => Ok(Object::Boolean(Boolean::new(false))),
+            },
+            Literal::String(string) => {
+                Ok(Object::String(StringObject::new(string)))
+            },
+        }
+    }
+    fn eval_prefix(&mut self, prefix: PrefixExpression) -> Result<Object, EvalError> {
+        let expression = self.eval(Node::Expression(*prefix.expression))?;
+        return self.eval_prefix_expression(prefix.prefix_op, expression);
+    }
+    fn eval_infix(&mut self, infix: InfixExpression) -> Result<Object, EvalError> {
+        let left = self.eval(Node::Expression(*infix.left_expression))?;
+        // && and || decide if the right side gets evaluated at all
+        if let InfixOp::AND | InfixOp::OR = infix.infix_op {
+            return self.eval_logical_expression(infix.infix_op, left, *infix.right_expression);
+        }
+        let right = self.eval(Node::Expression(*infix.right_expression))?;
+        return self.eval_infix_expression(infix.infix_op, left, right);
+    }
+    fn eval_interpolated_string(&mut self, string: InterpolatedString) -> Result<Object, EvalError> {
+        // every value is shown the way the REPL would print it
+        let mut output = String::new();
+        for part in string.parts {
+            let text = match part {
+                InterpolationPart::Literal(text, _) => text,
+                InterpolationPart::Expression(expression) => {
+                    let value = self.eval(Node::Expression(expression))?.inspect();
+                    self.take_work(value.len())?;
+                    value
                 },
-                Expression::IfExpression(iff) => self.eval_if_expression(iff),
-                Expression::Ident(ident) => self.eval_ident_expression(ident),
-                Expression::FunctionLiteral(func) => self.eval_function_expression(func),
-                Expression::CallExpression(call) => self.eval_call_expression(call),
-                express => Err(EvalError::FailedExpression(express)),
+            };
+            Evalulator::check_string_len(output.len().saturating_add(text.len()))?;
+            output.push_str(&text);
+        }
+        return Ok(Object::String(StringObject::new(output)));
+    }
+    fn eval_array_literal(&mut self, array: ArrayLiteral) -> Result<Object, EvalError> {
+        let elements = self.eval_expressions(array.elements)?;
+        return Ok(Object::Array(Array::new(elements)));
+    }
+    fn take_step(&mut self) -> Result<(), EvalError> {
+        return self.take_steps(1);
+    }
+    // charge an operation that copies or scans work bytes or elements
+    fn take_work(&mut self, work: usize) -> Result<(), EvalError> {
+        if work < WORK_PER_STEP {
+            return Ok(());
+        }
+        return self.take_steps((work / WORK_PER_STEP) as u64);
+    }
+    fn take_steps(&mut self, steps: u64) -> Result<(), EvalError> {
+        self.steps = self.steps.saturating_add(steps);
+        if let Some(limit) = self.step_limit {
+            if self.steps > limit {
+                return Err(EvalError::OutOfSteps(limit));
+            }
+        }
+        if let Some(limit) = self.time_limit {
+            // checked between nodes and as long running operations go
+            let spent = self.started.elapsed();
+            if spent > limit {
+                return Err(EvalError::Timeout(spent));
             }
         }
+        return Ok(());
     }
     fn eval_program(&mut self, program: Program) -> Result<Object, EvalError> {
         let mut result = Object::Null;
@@ -114,44 +336,183 @@ impl Evalulator {
                 true => Ok(Object::Boolean(Boolean::new(false))),
                 false => Ok(Object::Boolean(Boolean::new(true))),
             },
-            Object::Integer(_) => return Ok(Object::Boolean(Boolean::new(false))),
+            Object::Integer(_) | Object::Float(_) => return Ok(Object::Boolean(Boolean::new(false))),
             _ => return Ok(Object::Null),
         }
     }
 
     fn eval_minus_prefix_operator_expression(&mut self, express: Object) -> Result<Object, EvalError> {
-        if express.obj_type() != "Integer".to_string() {
-            return Err(EvalError::UnknownPrefix(express, PrefixOp::NEGATIVE));
+        let int = match &express {
+            Object::Integer(int) => int,
+            Object::Float(float) => return Ok(Object::Float(Float::new(-float.value))),
+            _ => return Err(EvalError::UnknownPrefix(express, PrefixOp::NEGATIVE)),
+        };
+        match int.checked_neg() {
+            Some(negated) => return Ok(Object::Integer(negated)),
+            None => return Err(EvalError::Overflow(format!("-({})", int.inspect()))),
         }
-        let output = "-".to_string();
-        let value = output + &express.inspect() as &str;
-        return Ok(Object::Integer(Integer::new(value as String)));
     }
     fn eval_infix_expression(&mut self, infix_op: InfixOp, left: Object, right: Object) -> Result<Object, EvalError> {
+        // an integer next to a float is promoted to a float
+        if let Object::Float(_) = left {
+            return self.eval_infix_float_expression(infix_op, left, right);
+        }
+        if let Object::Float(_) = right {
+            return self.eval_infix_float_expression(infix_op, left, right);
+        }
+        // "ab" * 3 and 3 * "ab" both repeat the string
+        match (&left, &infix_op, &right) {
+            (Object::String(string), InfixOp::MULTIPLY, Object::Integer(count))
+            | (Object::Integer(count), InfixOp::MULTIPLY, Object::String(string)) => {
+                return self.eval_string_repeat(string, count);
+            },
+            _ => {},
+        }
         if left.obj_type() != right.obj_type() {
             return Err(EvalError::Mismatched(left, infix_op, right));
         }
         if left.obj_type() == "Integer" && right.obj_type() == "Integer" {
             return self.eval_infix_integer_expression(infix_op, left, right);
         }
+        if let (Object::String(lstring), Object::String(rstring)) = (&left, &right) {
+            self.take_work(lstring.value.len().saturating_add(rstring.value.len()))?;
+            // comparisons go char by char on the code points
+            match infix_op {
+                InfixOp::PLUS => {
+                    Evalulator::check_string_len(lstring.value.len().saturating_add(rstring.value.len()))?;
+                    return Ok(Object::String(StringObject::new(format!("{}{}", lstring.value, rstring.value))));
+                },
+                InfixOp::LESSTHAN => return self.bool_to_boolean_object(lstring.value < rstring.value),
+                InfixOp::GREATERTHAN => return self.bool_to_boolean_object(lstring.value > rstring.value),
+                InfixOp::LESSEQUAL => return self.bool_to_boolean_object(lstring.value <= rstring.value),
+                InfixOp::GREATEREQUAL => return self.bool_to_boolean_object(lstring.value >= rstring.value),
+                _ => {},
+            }
+        }
         match infix_op {
             InfixOp::COMPARE =>  return self.bool_to_boolean_object(left == right),
             InfixOp::NEQUALS => return self.bool_to_boolean_object(left != right),
             _ => return Err(EvalError::UnknownInfix(left, infix_op, right)),
         }
     }
+    fn eval_logical_expression(&mut self, infix_op: InfixOp, left: Object, right: Expression) -> Result<Object, EvalError> {
+        // short circuit: only look at the right side when the left side doesnt settle it
+        let left_truth = self.is_truthy(left);
+        match infix_op {
+            InfixOp::AND if !left_truth => return self.bool_to_boolean_object(false),
+            InfixOp::OR if left_truth => return self.bool_to_boolean_object(true),
+            _ => {},
+        }
+        let right = self.eval(Node::Expression(right))?;
+        let right_truth = self.is_truthy(right);
+        return self.bool_to_boolean_object(right_truth);
+    }
     fn eval_infix_integer_expression(&mut self, infix_op: InfixOp, left: Object, right: Object) -> Result<Object, EvalError> {
-       let lvalue = left.inspect().parse::<i64>().unwrap();
-       let rvalue = right.inspect().parse::<i64>().unwrap();
-       match infix_op {
-           InfixOp::PLUS => return Ok(Object::Integer(Integer::from_num(lvalue + rvalue))),
-           InfixOp::SUBTRACT => return Ok(Object::Integer(Integer::from_num(lvalue - rvalue))),
-           InfixOp::MULTIPLY => return Ok(Object::Integer(Integer::from_num(lvalue * rvalue))),
-           InfixOp::DIVIDE => return Ok(Object::Integer(Integer::from_num(lvalue / rvalue))),
+       let (lvalue, rvalue) = match (&left, &right) {
+           (Object::Integer(lvalue), Object::Integer(rvalue)) => (lvalue, rvalue),
+           _ => return Err(EvalError::UnknownInfix(left, infix_op, right)),
+       };
+       // arithmetic that does not fit is an error, not a wrap around or a panic
+       let result = match infix_op {
+           InfixOp::PLUS => lvalue.checked_add(rvalue),
+           InfixOp::SUBTRACT => lvalue.checked_sub(rvalue),
+           InfixOp::MULTIPLY => lvalue.checked_mul(rvalue),
+           InfixOp::DIVIDE => {
+               if rvalue.is_zero() {
+                   return Err(EvalError::DivisionByZero(left));
+               }
+               lvalue.checked_div(rvalue)
+           },
            InfixOp::GREATERTHAN => return self.bool_to_boolean_object(lvalue > rvalue),
            InfixOp::LESSTHAN => return self.bool_to_boolean_object(lvalue < rvalue),
+           InfixOp::GREATEREQUAL => return self.bool_to_boolean_object(lvalue >= rvalue),
+           InfixOp::LESSEQUAL => return self.bool_to_boolean_object(lvalue <= rvalue),
            InfixOp::COMPARE => return self.bool_to_boolean_object(lvalue == rvalue),
            InfixOp::NEQUALS => return self.bool_to_boolean_object(lvalue != rvalue),
+           _ => return Err(EvalError::UnknownInfix(left, infix_op, right)),
+        };
+        match result {
+            Some(int) => return Ok(Object::Integer(int)),
+            None => return Err(EvalError::Overflow(format!("{} {} {}", lvalue.inspect(), infix_op, rvalue.inspect()))),
+        }
+    }
+    fn eval_string_repeat(&mut self, string: &StringObject, count: &Integer) -> Result<Object, EvalError> {
+        // a negative count repeats nothing, like zero
+        if count.is_negative() || count.is_zero() || string.value.is_empty() {
+            return Ok(Object::String(StringObject::new(String::new())));
+        }
+        let count = match count.as_i64().and_then(|count| usize::try_from(count).ok()) {
+            Some(count) => count,
+            None => return Err(EvalError::StringTooLong(usize::MAX)),
+        };
+        let length = string.value.len().saturating_mul(count);
+        Evalulator::check_string_len(length)?;
+        // grown and charged as it goes, so the limits stop a huge repeat part way before
+        // the memory for all of it is taken
+        let mut output = String::new();
+        let mut work = 0;
+        for _ in 0..count {
+            work += string.value.len();
+            if work >= WORK_PER_STEP {
+                self.take_work(work)?;
+                work %= WORK_PER_STEP;
+            }
+            // fail cleanly rather than abort when the memory is not there
+            if output.try_reserve(string.value.len()).is_err() {
+                return Err(EvalError::StringTooLong(length));
+            }
+            output.push_str(&string.value);
+        }
+        return Ok(Object::String(StringObject::new(output)));
+    }
+    fn eval_infix_float_expression(&mut self, infix_op: InfixOp, left: Object, right: Object) -> Result<Object, EvalError> {
+        let (lvalue, rvalue) = match (Evalulator::float_value(&left), Evalulator::float_value(&right)) {
+            (Some(lvalue), Some(rvalue)) => (lvalue, rvalue),
+            _ => return Err(EvalError::Mismatched(left, infix_op, right)),
+        };
+        // IEEE rules: dividing by zero gives inf or NaN and every comparison with NaN but != is false
+        let result = match infix_op {
+            InfixOp::PLUS => lvalue + rvalue,
+            InfixOp::SUBTRACT => lvalue - rvalue,
+            InfixOp::MULTIPLY => lvalue * rvalue,
+            InfixOp::DIVIDE => lvalue / rvalue,
+            InfixOp::GREATERTHAN => return self.bool_to_boolean_object(lvalue > rvalue),
+            InfixOp::LESSTHAN => return self.bool_to_boolean_object(lvalue < rvalue),
+            InfixOp::GREATEREQUAL => return self.bool_to_boolean_object(lvalue >= rvalue),
+            InfixOp::LESSEQUAL => return self.bool_to_boolean_object(lvalue <= rvalue),
+            InfixOp::COMPARE => return self.bool_to_boolean_object(lvalue == rvalue),
+            InfixOp::NEQUALS => return self.bool_to_boolean_object(lvalue != rvalue),
+            _ => return Err(EvalError::UnknownInfix(left, infix_op, right)),
+        };
+        return Ok(Object::Float(Float::new(result)));
+    }
+    fn check_string_len(length: usize) -> Result<(), EvalError> {
+        if length > MAX_STRING_LEN {
+            return Err(EvalError::StringTooLong(length));
+        }
+        return Ok(());
+    }
+    // bytes and elements a builtin may go through to handle object
+    fn work_size(object: &Object) -> usize {
+        match object {
+            Object::String(string) => return string.value.len(),
+            Object::Array(array) => return Evalulator::elements_work_size(&array.elements),
+            Object::Hash(hash) => {
+                return hash.pairs.values()
+                    .fold(hash.pairs.len(), |work, pair| work.saturating_add(Evalulator::work_size(&pair.value)));
+            },
+            _ => return 0,
+        }
+    }
+    fn elements_work_size(elements: &[Object]) -> usize {
+        return elements.iter()
+            .fold(elements.len(), |work, element| work.saturating_add(Evalulator::work_size(element)));
+    }
+    fn float_value(object: &Object) -> Option<f64> {
+        match object {
+            Object::Float(float) => return Some(float.value),
+            Object::Integer(int) => return Some(int.to_f64()),
+            _ => return None,
         }
     }
     fn bool_to_boolean_object(&mut self, input: bool) -> Result<Object, EvalError> {
@@ -205,12 +566,13 @@ impl Evalulator {
         return Ok(Object::Return(Return::new(result)));
     }
     fn eval_ident_expression(&self, ident: String) -> Result<Object, EvalError> {
-        self.environment.borrow_mut().print();
-        let value = self.environment.borrow_mut().get(&ident);
+        let value = self.environment.borrow().get(&ident);
         match value {
             Some(value) => Ok(value),
-            None => {
-                Err(EvalError::UnknownIdent(ident))
+            // bindings in the environment shadow the builtins
+            None => match builtins::get_builtin(&ident) {
+                Some(builtin) => Ok(builtin),
+                None => Err(EvalError::UnknownIdent(ident)),
             }
         }
     }
@@ -220,7 +582,7 @@ impl Evalulator {
                     Function {
                         parameters: func.parameters.clone(),
                         body: func.body.clone(),
-                        environment: self.environment.clone(),
+                        environment: Rc::clone(&self.environment),
                     }
                 )
         )
@@ -230,46 +592,188 @@ impl Evalulator {
         // eval the body then eval the expressions
         // then return the object from apply_function
         let function = self.eval(Node::Expression(*call.function))?;
-        let args = self.eval_expressions(call.arguments)?;
+        let args = self.eval_expressions(call.arguments.variables)?;
         return self.apply_function(function, args);
     }
-    fn eval_expressions(&mut self, func: Arguments) -> Result<Vec<Object>, EvalError> {
+    fn eval_expressions(&mut self, expressions: Vec<Expression>) -> Result<Vec<Object>, EvalError> {
         let mut result = Vec::<Object>::new();
-        for i in 0..func.len() {
-            let evaluated = self.eval(Node::Expression(func.variables[i].clone()))?;
+        for entry in expressions {
+            let evaluated = self.eval(Node::Expression(entry))?;
             result.push(evaluated);
         }
         return Ok(result);
     }
+    fn eval_hash_literal(&mut self, hash: HashLiteral) -> Result<Object, EvalError> {
+        let mut output = Hash::new();
+        for (key, value) in hash.pairs {
+            let key = self.eval(Node::Expression(key))?;
+            let hash_key = key.hash_key()?;
+            let value = self.eval(Node::Expression(value))?;
+            output.pairs.insert(hash_key, HashPair { key, value });
+        }
+        return Ok(Object::Hash(output));
+    }
+    fn eval_index_expression(&mut self, index: IndexExpression) -> Result<Object, EvalError> {
+        let left = self.eval(Node::Expression(*index.left))?;
+        let index = self.eval(Node::Expression(*index.index))?;
+        match (&left, &index) {
+            (Object::Array(array), Object::Integer(int)) => {
+                // anything outside of 0..len is Null rather than an error
+                match Evalulator::index_value(int).and_then(|index| array.elements.get(index)) {
+                    Some(element) => {
+                        self.take_work(Evalulator::work_size(element))?;
+                        return Ok(element.clone());
+                    },
+                    None => return Ok(Object::Null),
+                }
+            },
+            (Object::String(string), Object::Integer(int)) => {
+                // strings index by char, a one char string or Null like arrays
+                let index = match Evalulator::index_value(int) {
+                    Some(index) => index,
+                    None => return Ok(Object::Null),
+                };
+                // finding the char walks every one before it
+                self.take_work(index.min(string.value.len()))?;
+                match string.value.chars().nth(index) {
+                    Some(ch) => return Ok(Object::String(StringObject::new(ch.to_string()))),
+                    None => return Ok(Object::Null),
+                }
+            },
+            (Object::Hash(hash), _) => {
+                // missing keys are Null, unhashable keys are an error
+                let key = index.hash_key()?;
+                return Ok(hash.get(&key).unwrap_or(Object::Null));
+            },
+            _ => return Err(EvalError::UnsupportedIndex(left, index)),
+        }
+    }
+    // None when int is negative or too big to index anything
+    fn index_value(int: &Integer) -> Option<usize> {
+        return int.as_i64().and_then(|index| usize::try_from(index).ok());
+    }
+    fn eval_slice_expression(&mut self, slice: SliceExpression) -> Result<Object, EvalError> {
+        let left = self.eval(Node::Expression(*slice.left))?;
+        let start = self.eval_slice_bound(&left, slice.start)?;
+        let end = self.eval_slice_bound(&left, slice.end)?;
+        // bounds are clamped to 0..len like out of range indexes are Null, never an error
+        let clamp = |bound: Option<i64>, default: usize, len: usize| match bound {
+            Some(bound) => bound.clamp(0, len as i64) as usize,
+            None => default,
+        };
+        match &left {
+            Object::String(string) => {
+                // chars, not bytes
+                self.take_work(string.value.len())?;
+                let len = string.value.chars().count();
+                let (start, end) = (clamp(start, 0, len), clamp(end, len, len));
+                let output: String = string.value.chars().skip(start).take(end.saturating_sub(start)).collect();
+                self.take_work(output.len())?;
+                return Ok(Object::String(StringObject::new(output)));
+            },
+            Object::Array(array) => {
+                let len = array.elements.len();
+                let (start, end) = (clamp(start, 0, len), clamp(end, len, len));
+                let elements = &array.elements[start..end.max(start)];
+                self.take_work(Evalulator::elements_work_size(elements))?;
+                let elements = elements.to_vec();
+                return Ok(Object::Array(Array::new(elements)));
+            },
+            _ => return Err(EvalError::UnsupportedIndex(left.clone(), Object::Null)),
+        }
+    }
+    fn eval_slice_bound(&mut self, left: &Object, bound: Option<Box<Expression>>) -> Result<Option<i64>, EvalError> {
+        let bound = match bound {
+            Some(bound) => self.eval(Node::Expression(*bound))?,
+            None => return Ok(None),
+        };
+        match bound {
+            Object::Integer(int) => match int.as_i64() {
+                Some(bound) => return Ok(Some(bound)),
+                // past an i64 is past either end of anything, so it clamps like any other
+                // out of range bound
+                None if int.is_negative() => return Ok(Some(i64::MIN)),
+                None => return Ok(Some(i64::MAX)),
+            },
+            bound => return Err(EvalError::UnsupportedIndex(left.clone(), bound)),
+        }
+    }
     fn apply_function(&mut self, func: Object, args: Vec<Object>) -> Result<Object, EvalError> {
         // if func is a function object
-        // set the env to the current environment
-        // create a new environment from the func.environment, set it to our environment.outer
+        // create a new environment enclosed by func.environment, the one the function was
+        // defined in, not the one it is called from
         if let Object::Function(function) = func {
-            let env = &self.environment.clone();
-            let mut environment = Environment::new_enclosed_environment(Rc::clone(&Rc::new(function.environment)));
-            for (entry, parameter) in function.parameters.into_iter().enumerate() {
+            // functions have no name of their own, so the error shows the parameter list
+            if args.len() != function.parameters.variables.len() {
+                return Err(EvalError::WrongArgumentCount(
+                    format!("fn({})", function.parameters),
+                    function.parameters.variables.len(),
+                    args.len(),
+                ));
+            }
+            let mut environment = Environment::new_enclosed_environment(Rc::clone(&function.environment));
+            for (parameter, arg) in function.parameters.into_iter().zip(args.iter()) {
                 //for every parameter get the object from the args, push both into environment
-                environment.set(parameter.to_string(), args.get(entry).unwrap())?;
+                environment.set(parameter.to_string(), arg)?;
+            }
+            if self.call_depth >= self.max_call_depth {
+                return Err(EvalError::StackOverflow(self.max_call_depth));
+            }
+            // swap in the new environment for the body then put the callers environment back,
+            // errors included
+            let environment = self.new_environment(environment);
+            let env = std::mem::replace(&mut self.environment, environment);
+            self.call_depth += 1;
+            let evaluated = self.eval(Node::Statement(Statement::BlockStatement(function.body)));
+            self.call_depth -= 1;
+            self.environment = env;
+            // a return only leaves the function, not the caller
+            match evaluated? {
+                Object::Return(ret) => return Ok(*ret.value),
+                evaluated => return Ok(evaluated),
             }
-            // set our environment equal to our new environment
-            self.environment = RefCell::new(environment);
-            // eval func.body then set our environment back to our original environment before
-            // returning the newly evaluated environment
-            let evaluated = self.eval(Node::Statement(Statement::BlockStatement(function.body)))?;
-            self.environment = env.clone();
-            return Ok(evaluated);
         } 
+        if let Object::Builtin(builtin) = func {
+            // builtins run without coming back to eval, so they pay for their arguments up front
+            let work = args.iter().fold(0, |work: usize, arg| work.saturating_add(Evalulator::work_size(arg)));
+            self.take_work(work)?;
+            return (builtin.function)(args);
+        }
         return Err(EvalError::FailedObject(func));
     }
 }
+// the evalulator and its objects are not Send because of the Rc environments, but the thread
+// that hands them to the evaluation thread waits for it to finish and touches nothing in
+// between, so no Rc is ever used from two threads at once
+struct Exclusive<T>(T);
+unsafe impl<T> Send for Exclusive<T> {}
+impl<T> Exclusive<T> {
+    // taking self keeps closures from capturing the fields, which are not Send
+    fn into_inner(self) -> T {
+        return self.0;
+    }
+}
+// address of a local, the distance between two of them is the native stack used in between
+#[inline(always)]
+fn stack_address() -> usize {
+    let marker = 0u8;
+    return std::ptr::addr_of!(marker) as usize;
+}
+impl Drop for Evalulator {
+    fn drop(&mut self) {
+        self.clear_environments();
+    }
+}
 #[cfg(test)]
+// the tests build their tables with vec! and index them by position
+#[allow(clippy::useless_vec, clippy::clone_on_copy)]
 mod test{
     use std::vec;
-    use super::{Evalulator, EvalError};
+    use std::rc::Rc;
+    use super::{Evalulator, EvalError, DEFAULT_MAX_CALL_DEPTH, STACK_PER_CALL};
 
     use crate::token::ast::{Node, InfixOp, PrefixOp};
-    use crate::token::object::{Object, ObjectType, Integer, Boolean};
+    use crate::token::object::{Object, ObjectType, Integer, Float, Boolean, Array, StringObject, Hashable};
     use crate::token::{parser::Parser, token::Lexer};
 
     #[test]
@@ -368,6 +872,18 @@ mod test{
             "(1 < 2) == false",
             "(1 > 2) == true",
             "(1 > 2) == false",
+            "1 <= 2",
+            "2 <= 2",
+            "3 <= 2",
+            "1 >= 2",
+            "2 >= 2",
+            "3 >= 2",
+            "(1 <= 2) == true",
+            r#""abc" <= "abd""#,
+            r#""abc" <= "abc""#,
+            r#""b" <= "abc""#,
+            r#""b" >= "abc""#,
+            r#""ab" >= "abc""#,
         ];
         let expected =vec![
             true,
@@ -389,6 +905,18 @@ mod test{
             false,
             false,
             true,
+            true,
+            true,
+            false,
+            false,
+            true,
+            true,
+            true,
+            true,
+            true,
+            false,
+            true,
+            false,
         ];
 
         for i in 0..input.len() {
@@ -514,6 +1042,9 @@ mod test{
             "true + false;",
             "5; true + false; 5",
             "if (10>1) { true + false; }",
+            "let add = fn(x, y) { x + y }; add(1);",
+            "let add = fn(x, y) { x + y }; add(1, 2, 3);",
+            "fn() { 1 }(true)",
         ];
         let expected = vec![
             EvalError::Mismatched(Object::Integer(Integer::from_num(5)), InfixOp::PLUS, Object::Boolean(Boolean::new(true))),
@@ -522,6 +1053,9 @@ mod test{
             EvalError::UnknownInfix(Object::Boolean(Boolean::new(true)), InfixOp::PLUS, Object::Boolean(Boolean::new(false))),
             EvalError::UnknownInfix(Object::Boolean(Boolean::new(true)), InfixOp::PLUS, Object::Boolean(Boolean::new(false))),
             EvalError::UnknownInfix(Object::Boolean(Boolean::new(true)), InfixOp::PLUS, Object::Boolean(Boolean::new(false))),
+            EvalError::WrongArgumentCount("fn(x,y)".to_string(), 2, 1),
+            EvalError::WrongArgumentCount("fn(x,y)".to_string(), 2, 3),
+            EvalError::WrongArgumentCount("fn()".to_string(), 0, 1),
         ];
         for i in 0..input.len() {
             match test_eval(input[i].to_string()) {
@@ -582,6 +1116,29 @@ mod test{
         assert!(!evalulator.environment.borrow_mut().is_empty());
     }
     #[test]
+    fn test_environment_cycles_freed() -> () {
+        // f is bound in the global scope it captured, g in the scope of the call to make
+        let input = "let f = fn(n) { f(n) }; let make = fn() { let g = fn() { g() }; g }; let h = make();";
+        let mut evalulator = Evalulator::new();
+        let program = Parser::new(Lexer::new(input.to_string())).parse_program();
+        evalulator.eval(Node::Program(program)).unwrap();
+
+        let global = Rc::downgrade(&evalulator.environment);
+        let inner = match evalulator.environment.borrow().get("h") {
+            Some(Object::Function(function)) => Rc::downgrade(&function.environment),
+            other => panic!("h is not a function: {:?}", other),
+        };
+        evalulator.reset();
+        assert!(global.upgrade().is_none());
+        assert!(inner.upgrade().is_none());
+
+        let program = Parser::new(Lexer::new(input.to_string())).parse_program();
+        evalulator.eval(Node::Program(program)).unwrap();
+        let global = Rc::downgrade(&evalulator.environment);
+        drop(evalulator);
+        assert!(global.upgrade().is_none());
+    }
+    #[test]
     fn test_function_object() -> Result<(), EvalError> {
         let input = vec![
             "fn(x) { x + 2; };",
@@ -627,6 +1184,272 @@ mod test{
         Ok(())
     }
     #[test]
+    fn test_closures() -> Result<(), EvalError> {
+        let input = vec![
+            "let newAdder = fn(x) { fn(y) { x + y } }; let addTwo = newAdder(2); addTwo(3);",
+            // the function sees bindings made after it was defined
+            "let f = fn() { later }; let later = 7; f();",
+            "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(15);",
+            "let even = fn(n) { if (n == 0) { true } else { odd(n - 1) } };
+             let odd = fn(n) { if (n == 0) { false } else { even(n - 1) } };
+             if (even(10)) { 1 } else { 0 }",
+            // a closure returned from its defining function can still call itself
+            "let counter = fn() { let count = fn(n) { if (n == 0) { 0 } else { 1 + count(n - 1) } }; count };
+             let c = counter(); c(4);",
+            // a return inside a function does not end the caller
+            "let f = fn() { return 1; }; f(); 9;",
+            // the arguments do not leak into the callers scope
+            "let x = 3; let f = fn(x) { x * 10 }; f(1) + x;",
+        ];
+
+        let expected = vec![
+           5,
+           7,
+           610,
+           1,
+           4,
+           9,
+           13,
+        ];
+        for i in 0..input.len() {
+            let evaluated = test_eval(input[i].to_string())?;
+            if !test_integer_object(&evaluated, expected[i]) {
+                println!("iteration: {}", &i);
+                return Err(EvalError::FailedEval(format!("Expected: {}, got: {}", &expected[i], &evaluated)));
+            }
+        }
+
+        // an error inside a function puts the callers environment back
+        let mut evalulator = Evalulator::new();
+        let l = Lexer::new("let x = 1; let f = fn(y) { missing }; f(2);".to_string());
+        let program = Parser::new(l).parse_program();
+        assert!(evalulator.eval(Node::Program(program)).is_err());
+        assert_eq!(evalulator.environment.borrow().get("y"), None);
+        assert!(evalulator.environment.borrow().get("x").is_some());
+        Ok(())
+    }
+    #[test]
+    fn test_default_call_depth() -> Result<(), EvalError> {
+        // the default evaluator brings its own stack, so the small one of a test thread is
+        // enough for the whole default depth, in debug builds too
+        let mut evalulator = Evalulator::new();
+        let input = vec![
+            ("let down = fn(n) { if (n == 0) { 0 } else { 1 + down(n - 1) } }; down(500);", Ok(500)),
+            ("down(999);", Ok(999)),
+            ("down(1000);", Err(EvalError::StackOverflow(DEFAULT_MAX_CALL_DEPTH))),
+            ("let f = fn(n) { f(n + 1) }; f(0);", Err(EvalError::StackOverflow(DEFAULT_MAX_CALL_DEPTH))),
+            ("let g = fn(n) { 1 + [{\"a\": g(n + 1)}][0][\"a\"] }; g(0);", Err(EvalError::StackOverflow(DEFAULT_MAX_CALL_DEPTH))),
+        ];
+        for (entry, expected) in input {
+            let l = Lexer::new(entry.to_string());
+            let program = Parser::new(l).parse_program();
+            match (evalulator.eval(Node::Program(program)), expected) {
+                (Ok(evaluated), Ok(expected)) => assert!(test_integer_object(&evaluated, expected), "input: {}", entry),
+                (Err(error), Err(expected)) => assert_eq!(error, expected, "input: {}", entry),
+                (evaluated, _) => panic!("input: {}, got: {:?}", entry, evaluated),
+            }
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_stack_exhausted() -> () {
+        // nesting without calls never moves the call depth, the stack check catches it
+        let depth = 5000;
+        let input = format!("{}1{}", "-(".repeat(depth), ")".repeat(depth));
+        // the parser recurses too and runs on the caller's stack
+        let program = std::thread::Builder::new()
+            .stack_size(256 << 20)
+            .spawn(move || Parser::new(Lexer::new(input)).parse_program())
+            .unwrap().join().unwrap();
+        let mut evalulator = Evalulator::new();
+        evalulator.max_call_depth = 0;
+        assert_eq!(evalulator.eval(Node::Program(program)), Err(EvalError::StackExhausted(STACK_PER_CALL)));
+        // a single level is fine
+        let program = Parser::new(Lexer::new("-(1)".to_string())).parse_program();
+        assert!(test_integer_object(&evalulator.eval(Node::Program(program)).unwrap(), -1));
+    }
+    #[test]
+    fn test_call_depth_limit() -> Result<(), EvalError> {
+        // down(n) makes n + 1 nested calls
+        let mut evalulator = Evalulator::new();
+        evalulator.max_call_depth = 50;
+        let input = vec![
+            ("let down = fn(n) { if (n == 0) { 0 } else { 1 + down(n - 1) } }; down(49);", Ok(49)),
+            ("down(50);", Err(EvalError::StackOverflow(50))),
+            ("let forever = fn(n) { forever(n + 1) }; forever(0);", Err(EvalError::StackOverflow(50))),
+            // the depth unwinds after an error
+            ("down(5);", Ok(5)),
+        ];
+        for (entry, expected) in input {
+            let l = Lexer::new(entry.to_string());
+            let program = Parser::new(l).parse_program();
+            let evaluated = evalulator.eval(Node::Program(program));
+            match (evaluated, expected) {
+                (Ok(evaluated), Ok(expected)) => assert!(test_integer_object(&evaluated, expected), "input: {}", entry),
+                (Err(error), Err(expected)) => assert_eq!(error, expected, "input: {}", entry),
+                (evaluated, _) => panic!("input: {}, got: {:?}", entry, evaluated),
+            }
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_step_and_time_limits() -> Result<(), EvalError> {
+        let fib = "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } };";
+        let run = |evalulator: &mut Evalulator, input: String| {
+            let l = Lexer::new(input);
+            let program = Parser::new(l).parse_program();
+            return evalulator.eval(Node::Program(program));
+        };
+
+        // program, let statement, infix expression, two literals
+        let mut evalulator = Evalulator::new();
+        run(&mut evalulator, "let x = 1 + 2;".to_string())?;
+        assert_eq!(evalulator.steps(), 5);
+
+        evalulator.step_limit = Some(4);
+        assert_eq!(run(&mut evalulator, "let x = 1 + 2;".to_string()), Err(EvalError::OutOfSteps(4)));
+        // the next program starts with a fresh budget
+        evalulator.step_limit = Some(1000);
+        assert!(test_integer_object(&run(&mut evalulator, format!("{} fib(5)", fib))?, 5));
+        assert_eq!(run(&mut evalulator, "fib(12)".to_string()), Err(EvalError::OutOfSteps(1000)));
+
+        // wide rather than deep, so it runs out of time long before it runs out of stack
+        let wide = "let wide = fn(n) { if (n == 0) { 0 } else { wide(n - 1) + wide(n - 1) + wide(n - 1) } };";
+        let mut evalulator = Evalulator::new();
+        evalulator.time_limit = Some(std::time::Duration::from_millis(20));
+        match run(&mut evalulator, format!("{} wide(15)", wide)) {
+            Err(EvalError::Timeout(spent)) => assert!(spent.as_millis() >= 20),
+            evaluated => panic!("expected a timeout, got: {:?}", evaluated),
+        }
+
+        // repeating, joining, indexing, slicing and builtins cost a step for every 1024 bytes
+        // or elements they go through
+        let mut evalulator = Evalulator::new();
+        evalulator.step_limit = Some(100);
+        run(&mut evalulator, r#"let s = "ab" * 40000; let a = [s, s];"#.to_string())?;
+        assert!(evalulator.steps() > 78);
+        let input = vec![
+            ("len(s)", Ok("80000")),
+            (r#""ab" * 100000"#, Err(EvalError::OutOfSteps(100))),
+            ("len(s); len(s);", Err(EvalError::OutOfSteps(100))),
+            ("s + s", Err(EvalError::OutOfSteps(100))),
+            ("s[79999]", Ok("b")),
+            ("s[79999]; s[79999];", Err(EvalError::OutOfSteps(100))),
+            ("s[79990:]", Ok("ababababab")),
+            ("s[1:]", Err(EvalError::OutOfSteps(100))),
+            ("a[0]; a[1];", Err(EvalError::OutOfSteps(100))),
+            ("a[0:]", Err(EvalError::OutOfSteps(100))),
+            ("a[2:]", Ok("[]")),
+        ];
+        for (entry, expected) in input {
+            let evaluated = run(&mut evalulator, entry.to_string()).map(|evaluated| evaluated.inspect());
+            assert_eq!(evaluated, expected.map(|expected| expected.to_string()), "input: {}", entry);
+        }
+        Ok(())
+    }
+    #[test]
+    #[cfg(not(feature = "bigint"))]
+    fn test_integer_overflow() -> Result<(), EvalError> {
+        let input = vec![
+            ("9223372036854775807 + 1", Err(EvalError::Overflow("9223372036854775807 + 1".to_string()))),
+            ("-9223372036854775807 - 2", Err(EvalError::Overflow("-9223372036854775807 - 2".to_string()))),
+            ("4611686018427387904 * 2", Err(EvalError::Overflow("4611686018427387904 * 2".to_string()))),
+            ("let min = -9223372036854775807 - 1; min / -1", Err(EvalError::Overflow("-9223372036854775808 / -1".to_string()))),
+            ("let min = -9223372036854775807 - 1; -min", Err(EvalError::Overflow("-(-9223372036854775808)".to_string()))),
+            ("5 / 0", Err(EvalError::DivisionByZero(Object::Integer(Integer::from_num(5))))),
+            ("let z = 1 - 1; 10 / z", Err(EvalError::DivisionByZero(Object::Integer(Integer::from_num(10))))),
+            // right at the edges is fine
+            ("9223372036854775806 + 1", Ok(i64::MAX)),
+            ("-9223372036854775807 - 1", Ok(i64::MIN)),
+            ("- -5", Ok(5)),
+            ("-7 / 2", Ok(-3)),
+        ];
+        for (entry, expected) in input {
+            match (test_eval(entry.to_string()), expected) {
+                (Ok(evaluated), Ok(expected)) => assert!(test_integer_object(&evaluated, expected), "input: {}", entry),
+                (Err(error), Err(expected)) => assert_eq!(error, expected, "input: {}", entry),
+                (evaluated, _) => panic!("input: {}, got: {:?}", entry, evaluated),
+            }
+        }
+        Ok(())
+    }
+    #[test]
+    #[cfg(feature = "bigint")]
+    fn test_big_integers() -> Result<(), EvalError> {
+        let input = vec![
+            ("9223372036854775807 + 1", "9223372036854775808"),
+            ("-9223372036854775807 - 2", "-9223372036854775809"),
+            ("99999999999999999999 * 99999999999999999999", "9999999999999999999800000000000000000001"),
+            ("let min = -9223372036854775807 - 1; min / -1", "9223372036854775808"),
+            ("let min = -9223372036854775807 - 1; -min", "9223372036854775808"),
+            ("99999999999999999999 / 99999999999999999999", "1"),
+            ("99999999999999999999 > 9223372036854775807", "true"),
+            ("-99999999999999999999 < -9223372036854775807", "true"),
+            ("99999999999999999999 == 99999999999999999999", "true"),
+            ("{99999999999999999999: 1, 9223372036854775807: 2}[99999999999999999999]", "1"),
+            ("[1, 2][99999999999999999999]", "Null"),
+            ("[1, 2][-99999999999999999999]", "Null"),
+            (r#""ab"[99999999999999999999]"#, "Null"),
+            ("[1, 2, 3][-99999999999999999999:99999999999999999999]", "[1, 2, 3]"),
+            ("[1, 2, 3][99999999999999999999:]", "[]"),
+            (r#""ab" * -99999999999999999999"#, ""),
+        ];
+        for (entry, expected) in input {
+            assert_eq!(test_eval(entry.to_string())?.inspect(), expected, "input: {}", entry);
+        }
+
+        // results that fit go back to a plain i64
+        let evaluated = test_eval("(9223372036854775807 + 1) - 1".to_string())?;
+        assert_eq!(evaluated, Object::Integer(Integer::from_num(i64::MAX)));
+        // the saturated value is never handed out
+        match test_eval("9223372036854775807 + 1".to_string())? {
+            Object::Integer(int) => assert_eq!(int.as_i64(), None),
+            evaluated => panic!("expected an integer, got: {:?}", evaluated),
+        }
+        assert!(matches!(test_eval(r#""ab" * 99999999999999999999"#.to_string()), Err(EvalError::StringTooLong(_))));
+        assert_eq!(test_eval("1 / 0".to_string()), Err(EvalError::DivisionByZero(Object::Integer(Integer::from_num(1)))));
+        Ok(())
+    }
+    #[test]
+    fn test_floats() -> Result<(), EvalError> {
+        let input = vec![
+            ("3.14", "3.14"),
+            ("1e-9", "1e-9"),
+            (".5 + .25", "0.75"),
+            ("0.1 + 0.2", "0.30000000000000004"),
+            ("2.0", "2.0"),
+            ("-2.5 * 2", "-5.0"),
+            ("1 + 0.5", "1.5"),
+            ("7 / 2.0", "3.5"),
+            ("7 / 2", "3"),
+            ("1e300 * 1e300", "inf"),
+            ("-1.0 / 0", "-inf"),
+            ("0.0 / 0", "NaN"),
+            ("let nan = 0.0 / 0; nan == nan", "false"),
+            ("let nan = 0.0 / 0; nan != nan", "true"),
+            ("let nan = 0.0 / 0; nan < 1 || nan >= 1", "false"),
+            ("1 == 1.0", "true"),
+            ("2 > 1.5", "true"),
+            ("1.5 <= 1", "false"),
+            ("!1.5", "false"),
+            ("if (0.0) { 1 } else { 2 }", "1"),
+        ];
+        for (entry, expected) in input {
+            assert_eq!(test_eval(entry.to_string())?.inspect(), expected, "input: {}", entry);
+        }
+
+        // printed floats read back as the same value
+        for value in [0.1, 1.0 / 3.0, 1e21, 5e-324, 123456.789] {
+            let printed = Object::Float(Float::new(value)).inspect();
+            assert_eq!(test_eval(printed.clone())?, Object::Float(Float::new(value)), "printed: {}", printed);
+        }
+
+        assert_eq!(test_eval("{1.5: 1}".to_string()), Err(EvalError::UnusableHashKey(Object::Float(Float::new(1.5)))));
+        assert!(matches!(test_eval("1.5 + true".to_string()), Err(EvalError::Mismatched(..))));
+        assert!(matches!(test_eval("[1, 2][1.0]".to_string()), Err(EvalError::UnsupportedIndex(..))));
+        Ok(())
+    }
+    #[test]
     fn test_string_literal() -> Result<(), EvalError> {
         let input = r#""hello world!""#.to_string();
 
@@ -641,4 +1464,332 @@ mod test{
         Ok(())
     }
 
+    #[test]
+    fn test_string_operations() -> Result<(), EvalError> {
+        let input = vec![
+            (r#""Hello" + " " + "World!""#, "Hello World!"),
+            (r#"let s = "ab"; s + s"#, "abab"),
+            (r#""" + """#, ""),
+            (r#""apple" < "banana""#, "true"),
+            (r#""apple" > "apricot""#, "false"),
+            (r#""a" < "ab""#, "true"),
+            (r#""Z" < "a""#, "true"),
+            (r#""é" > "z""#, "true"),
+            (r#""abc" <= "abc""#, "true"),
+            (r#""abd" >= "abc""#, "true"),
+            (r#""ab" * 3"#, "ababab"),
+            (r#"3 * "ab""#, "ababab"),
+            (r#""ab" * 0"#, ""),
+            (r#""ab" * -2"#, ""),
+            (r#""" * 1000000000000"#, ""),
+            (r#""héllo"[1]"#, "é"),
+            (r#""hello"[1:3]"#, "el"),
+            (r#""hello"[:2]"#, "he"),
+            (r#""hello"[3:]"#, "lo"),
+            (r#""hello"[:]"#, "hello"),
+            (r#""héllo wörld"[1:8]"#, "éllo wö"),
+            (r#""hello"[-5:100]"#, "hello"),
+            (r#""hello"[4:2]"#, ""),
+            (r#"let s = "hello"; s[1:len(s) - 1]"#, "ell"),
+            ("[1, 2, 3, 4][1:3]", "[2, 3]"),
+            ("[1, 2, 3][2:]", "[3]"),
+            ("[1, 2, 3][5:]", "[]"),
+        ];
+        for (entry, expected) in input {
+            assert_eq!(test_eval(entry.to_string())?.inspect(), expected, "input: {}", entry);
+        }
+
+        let string = |value: &str| Object::String(StringObject::new(value.to_string()));
+        let input = vec![
+            (r#""a" - "b""#, EvalError::UnknownInfix(string("a"), InfixOp::SUBTRACT, string("b"))),
+            (r#""a" + 1"#, EvalError::Mismatched(string("a"), InfixOp::PLUS, Object::Integer(Integer::from_num(1)))),
+            (r#""a" * "b""#, EvalError::UnknownInfix(string("a"), InfixOp::MULTIPLY, string("b"))),
+            (r#""abc"["a":]"#, EvalError::UnsupportedIndex(string("abc"), string("a"))),
+            ("5[1:2]", EvalError::UnsupportedIndex(Object::Integer(Integer::from_num(5)), Object::Null)),
+            (r#""a" * 1073741825"#, EvalError::StringTooLong(1073741825)),
+        ];
+        for (entry, expected) in input {
+            assert_eq!(test_eval(entry.to_string()), Err(expected), "input: {}", entry);
+        }
+        // far too long whichever way the length is counted
+        assert!(matches!(test_eval(r#""ab" * 9223372036854775807"#.to_string()), Err(EvalError::StringTooLong(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_interpolation() -> Result<(), EvalError> {
+        let input = vec![
+            (r#"let name = "Ann"; let count = 2; "Hello ${name}, you have ${count + 1} items""#, "Hello Ann, you have 3 items"),
+            (r#""${1.5 * 2} ${true} ${[1, "a"]} ${{"k": 2}["k"]}""#, "3.0 true [1, a] 2"),
+            (r#"let f = fn(x) { "<${x}>" }; "${f("${f(1)}")}""#, "<<1>>"),
+            (r#""${[][0]}""#, "Null"),
+            (r#""cost: \${price} $5""#, "cost: ${price} $5"),
+            (r#""plain""#, "plain"),
+        ];
+        for (entry, expected) in input {
+            assert_eq!(test_eval(entry.to_string())?.inspect(), expected, "input: {}", entry);
+        }
+
+        // an error inside the ${ } is the error of the whole string
+        assert_eq!(test_eval(r#""a ${nope} b""#.to_string()), Err(EvalError::UnknownIdent("nope".to_string())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_logical_operators() -> Result<(), EvalError> {
+        let input = vec![
+            "true && true",
+            "true && false",
+            "false || true",
+            "false || false",
+            "1 < 2 && 2 < 3",
+            "1 > 2 || 2 > 3",
+            "if (1 < 2 && 3 > 2) { true } else { false }",
+            // the right side is never evaluated, so the unknown identifier is fine
+            "false && unknown",
+            "true || unknown",
+            "let a = 5; a > 1 && a < 10",
+            "1 && if (false) { 1 }",
+        ];
+        let expected = vec![
+            true,
+            false,
+            true,
+            false,
+            true,
+            false,
+            true,
+            false,
+            true,
+            true,
+            false,
+        ];
+
+        for i in 0..input.len() {
+            let evaluated = test_eval(input[i].to_string())?;
+            if !test_boolean_object(&evaluated, expected[i]) {
+                println!("iteration: {}", &i);
+                return Err(EvalError::FailedEval(format!("Expected: {}, got: {}", &expected[i], &evaluated)));
+            }
+        }
+
+        // when the left side doesnt settle it the right side is evaluated
+        assert_eq!(test_eval("true && unknown".to_string()), Err(EvalError::UnknownIdent("unknown".to_string())));
+        Ok(())
+    }
+    #[test]
+    fn test_array_literals() -> Result<(), EvalError> {
+        let input = "[1, 2 * 2, 3 + 3]".to_string();
+
+        let evaluated = test_eval(input)?;
+        let expected = Object::Array(Array::new(vec![
+            Object::Integer(Integer::from_num(1)),
+            Object::Integer(Integer::from_num(4)),
+            Object::Integer(Integer::from_num(6)),
+        ]));
+        assert_eq!(evaluated, expected);
+        assert_eq!(evaluated.inspect(), "[1, 4, 6]");
+        Ok(())
+    }
+    #[test]
+    fn test_array_index_expressions() -> Result<(), EvalError> {
+        let input = vec![
+            "[1, 2, 3][0]",
+            "[1, 2, 3][1]",
+            "[1, 2, 3][2]",
+            "let i = 0; [1][i];",
+            "[1, 2, 3][1 + 1];",
+            "let myArray = [1, 2, 3]; myArray[2];",
+            "let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];",
+            "let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]",
+            "[[1, 2], [3, 4]][1][0]",
+            "[1, 2, 3][3]",
+            "[1, 2, 3][-1]",
+            "[][0]",
+        ];
+        let expected = vec![
+            Object::Integer(Integer::from_num(1)),
+            Object::Integer(Integer::from_num(2)),
+            Object::Integer(Integer::from_num(3)),
+            Object::Integer(Integer::from_num(1)),
+            Object::Integer(Integer::from_num(3)),
+            Object::Integer(Integer::from_num(3)),
+            Object::Integer(Integer::from_num(6)),
+            Object::Integer(Integer::from_num(2)),
+            Object::Integer(Integer::from_num(3)),
+            Object::Null,
+            Object::Null,
+            Object::Null,
+        ];
+        for i in 0..input.len() {
+            let evaluated = test_eval(input[i].to_string())?;
+            if !test_object(evaluated.clone(), expected[i].clone()) {
+                println!("iteration: {}", &i);
+                return Err(EvalError::FailedEval(format!("Expected: {}, got: {}", &expected[i], &evaluated)));
+            }
+        }
+
+        let input = vec![
+            (r#""héllo"[1]"#, "é"),
+            (r#""名前😀"[2]"#, "😀"),
+            (r#"let s = "abc"; s[len(s) - 1]"#, "c"),
+            (r#""abc"[3]"#, "Null"),
+            (r#""abc"[-1]"#, "Null"),
+            (r#"""[0]"#, "Null"),
+        ];
+        for (entry, expected) in input {
+            assert_eq!(test_eval(entry.to_string())?.inspect(), expected, "input: {}", entry);
+        }
+
+        assert_eq!(
+            test_eval("5[0]".to_string()),
+            Err(EvalError::UnsupportedIndex(Object::Integer(Integer::from_num(5)), Object::Integer(Integer::from_num(0))))
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_hash_literals() -> Result<(), EvalError> {
+        let input = r#"
+        let two = "two";
+        {
+            "one": 10 - 9,
+            two: 1 + 1,
+            "three": 6 / 2,
+            4: 4,
+            true: 5,
+            false: 6
+        }
+        "#.to_string();
+
+        let evaluated = test_eval(input)?;
+        let hash = match evaluated {
+            Object::Hash(hash) => hash,
+            other => return Err(EvalError::FailedEval(format!("Expected a Hash, got: {}", other))),
+        };
+
+        let expected = vec![
+            (StringObject::new("one".to_string()).hash_key(), 1),
+            (StringObject::new("two".to_string()).hash_key(), 2),
+            (StringObject::new("three".to_string()).hash_key(), 3),
+            (Integer::from_num(4).hash_key(), 4),
+            (Boolean::new(true).hash_key(), 5),
+            (Boolean::new(false).hash_key(), 6),
+        ];
+        assert_eq!(hash.pairs.len(), expected.len());
+        for (key, value) in expected {
+            let found = hash.get(&key).ok_or_else(|| EvalError::FailedEval(format!("Missing key: {:?}", key)))?;
+            assert!(test_integer_object(&found, value));
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_hash_index_expressions() -> Result<(), EvalError> {
+        let input = vec![
+            r#"{"foo": 5}["foo"]"#,
+            r#"{"foo": 5}["bar"]"#,
+            r#"let key = "foo"; {"foo": 5}[key]"#,
+            r#"{}["foo"]"#,
+            "{5: 5}[5]",
+            "{true: 5}[true]",
+            "{false: 5}[false]",
+        ];
+        let expected = vec![
+            Object::Integer(Integer::from_num(5)),
+            Object::Null,
+            Object::Integer(Integer::from_num(5)),
+            Object::Null,
+            Object::Integer(Integer::from_num(5)),
+            Object::Integer(Integer::from_num(5)),
+            Object::Integer(Integer::from_num(5)),
+        ];
+        for i in 0..input.len() {
+            let evaluated = test_eval(input[i].to_string())?;
+            if !test_object(evaluated.clone(), expected[i].clone()) {
+                println!("iteration: {}", &i);
+                return Err(EvalError::FailedEval(format!("Expected: {}, got: {}", &expected[i], &evaluated)));
+            }
+        }
+
+        assert_eq!(test_eval(r#"{1: true, "name": "x"}"#.to_string())?.inspect(), "{1: true, name: x}");
+
+        // functions and arrays cant be used as keys
+        let input = vec![
+            r#"{"name": "Monkey"}[fn(x) { x }];"#,
+            "{[1]: 1}",
+        ];
+        for entry in input {
+            match test_eval(entry.to_string()) {
+                Err(EvalError::UnusableHashKey(_)) => {},
+                other => panic!("expected UnusableHashKey, got: {:?}", other),
+            }
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_builtin_functions() -> Result<(), EvalError> {
+        let input = vec![
+            r#"len("")"#,
+            r#"len("four")"#,
+            r#"len("hello world")"#,
+            r#"len("héllo wörld")"#,
+            r#"len("名前😀")"#,
+            "len([1, 2, 3])",
+            "len([])",
+            "first([1, 2, 3])",
+            "first([])",
+            "last([1, 2, 3])",
+            "last([])",
+            "rest([1, 2, 3])",
+            "rest([])",
+            "push([], 1)",
+            "let a = [1]; push(a, 2); a",
+            r#"puts("hello", 1)"#,
+            "let len = fn(x) { 42 }; len([1])",
+        ];
+        let expected = vec![
+            "0",
+            "4",
+            "11",
+            "11",
+            "3",
+            "3",
+            "0",
+            "1",
+            "Null",
+            "3",
+            "Null",
+            "[2, 3]",
+            "Null",
+            "[1]",
+            "[1]",
+            "Null",
+            "42",
+        ];
+        for i in 0..input.len() {
+            let evaluated = test_eval(input[i].to_string())?;
+            if evaluated.inspect() != expected[i] {
+                println!("iteration: {}", &i);
+                return Err(EvalError::FailedEval(format!("Expected: {}, got: {}", &expected[i], &evaluated)));
+            }
+        }
+
+        let input = vec![
+            "len(1)",
+            r#"len("one", "two")"#,
+            "first(1)",
+            "push([1])",
+            "rest()",
+        ];
+        let expected = vec![
+            EvalError::WrongArgumentType("len".to_string(), Object::Integer(Integer::from_num(1))),
+            EvalError::WrongArgumentCount("len".to_string(), 1, 2),
+            EvalError::WrongArgumentType("first".to_string(), Object::Integer(Integer::from_num(1))),
+            EvalError::WrongArgumentCount("push".to_string(), 2, 1),
+            EvalError::WrongArgumentCount("rest".to_string(), 1, 0),
+        ];
+        for (entry, error) in input.into_iter().zip(expected) {
+            assert_eq!(test_eval(entry.to_string()), Err(error));
+        }
+        Ok(())
+    }
 }
@@ -4,3 +4,5 @@ pub mod evaluator;
 pub mod repl;
 pub mod ast;
 pub mod parser;
+pub mod builtins;
+pub mod script;
@@ -1,6 +1,9 @@
-use std::{fmt::Display, i64, collections::HashMap};
+use std::{fmt::Display, i64, collections::HashMap, collections::BTreeMap};
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::cmp::Ordering;
+#[cfg(feature = "bigint")]
+use num_bigint::{BigInt, Sign};
 use crate::token::evaluator::EvalError;
 
 use super::ast::{Parameters, BlockStatement};
@@ -11,36 +14,56 @@ pub trait ObjectType {
 #[derive(PartialEq, Clone, Debug)]
 pub enum Object {
     Integer(Integer),
+    Float(Float),
     Boolean(Boolean),
     Return(Return),
     String(StringObject),
     Error(String),
     Function(Function),
+    Array(Array),
+    Hash(Hash),
+    Builtin(Builtin),
     Null,
 }
 impl Object {
     pub fn obj_type(&self) -> String {
         match self {
             Object::Integer(_) => return "Integer".to_string(),
+            Object::Float(_) => return "Float".to_string(),
             Object::Boolean(_) => return "Bool".to_string(),
             Object::Return(_) => return "Return".to_string(),
             Object::Error(_) => return "Error".to_string(),
             Object::Function(_) => return "Function".to_string(),
             Object::String(_) => return "String".to_string(),
+            Object::Array(_) => return "Array".to_string(),
+            Object::Hash(_) => return "Hash".to_string(),
+            Object::Builtin(_) => return "Builtin".to_string(),
             Object::Null => return "Null".to_string(),
         }
     }
-
+    pub fn hash_key(&self) -> Result<HashKey, EvalError> {
+        // only values with a stable identity can be used as hash keys
+        match self {
+            Object::Integer(int) => return Ok(int.hash_key()),
+            Object::Boolean(bool) => return Ok(bool.hash_key()),
+            Object::String(string) => return Ok(string.hash_key()),
+            _ => return Err(EvalError::UnusableHashKey(self.clone())),
+        }
+    }
 }
 impl ObjectType for Object {
         fn inspect(&self) -> String {
         match self {
             Object::Integer(int) => return int.inspect(),
+            Object::Float(float) => return float.inspect(),
             Object::Boolean(bool) => return bool.inspect(),
             Object::Return(ret) => return ret.inspect(),
             Object::String(string) => return string.inspect(),
             Object::Error(str) => return str.clone(),
             Object::Function(funct) => return funct.inspect(),
+            Object::Array(array) => return array.inspect(),
+            Object::Hash(hash) => return hash.inspect(),
+            Object::Builtin(builtin) => return builtin.inspect(),
             Object::Null => return "Null".to_string(),
         }
     }
@@ -49,49 +72,211 @@ impl Display for Object {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Object::Integer(int) => int.fmt(f),
+            Object::Float(float) => float.fmt(f),
             Object::Boolean(bool) => bool.fmt(f),
             Object::Return(ret) => ret.fmt(f),
             Object::String(string) => string.fmt(f),
             Object::Error(str) => write!(f, "{}", str),
             Object::Function(funct) => funct.fmt(f),
+            Object::Array(array) => array.fmt(f),
+            Object::Hash(hash) => hash.fmt(f),
+            Object::Builtin(builtin) => builtin.fmt(f),
             Object::Null => write!(f, "Null value"),
         }
     }
 }
 #[derive(PartialEq, Clone, Debug)]
 pub struct Integer {
-    pub value: i64,
+    // saturated at i64::MIN or i64::MAX once the integer has grown past them, so it is only
+    // handed out through as_i64
+    value: i64,
+    // the whole value, only set while it does not fit in value
+    #[cfg(feature = "bigint")]
+    big: Option<BigInt>,
 }
 impl Default for Integer {
     fn default() -> Self {
         Integer { 
             value: -1,
+            #[cfg(feature = "bigint")]
+            big: None,
         }
     }
 }
 impl Integer {
-    pub fn new(num: String) -> Integer {
-        let mut int = Integer::default();
-        int.value = num.parse::<i64>().unwrap();
-        int
+    // None when num is not a number or does not fit
+    pub fn parse(num: &str) -> Option<Integer> {
+        #[cfg(feature = "bigint")]
+        return num.parse::<BigInt>().ok().map(Integer::from_big);
+        #[cfg(not(feature = "bigint"))]
+        return num.parse::<i64>().ok().map(Integer::from_num);
     }
-    pub fn get_value(&self) -> i64 {
-        return self.value;
+    // None once the integer has grown past an i64
+    pub fn as_i64(&self) -> Option<i64> {
+        if self.is_big() {
+            return None;
+        }
+        return Some(self.value);
     }
     pub fn from_num(num: i64) -> Integer {
         let mut int = Integer::default();
         int.value = num;
         int
     }
+    #[cfg(feature = "bigint")]
+    pub fn from_big(num: BigInt) -> Integer {
+        // anything that fits goes back to a plain i64
+        match i64::try_from(&num) {
+            Ok(value) => return Integer::from_num(value),
+            Err(_) => {
+                let mut int = Integer::default();
+                int.value = if num.sign() == Sign::Minus { i64::MIN } else { i64::MAX };
+                int.big = Some(num);
+                return int;
+            },
+        }
+    }
+    #[cfg(feature = "bigint")]
+    pub fn to_big(&self) -> BigInt {
+        match &self.big {
+            Some(big) => return big.clone(),
+            None => return BigInt::from(self.value),
+        }
+    }
+    pub fn is_big(&self) -> bool {
+        #[cfg(feature = "bigint")]
+        return self.big.is_some();
+        #[cfg(not(feature = "bigint"))]
+        return false;
+    }
+    pub fn is_zero(&self) -> bool {
+        return self.value == 0;
+    }
+    // saturating keeps the sign, so this holds for big integers too
+    pub fn is_negative(&self) -> bool {
+        return self.value < 0;
+    }
+    // the nearest float, used when an integer meets a float
+    pub fn to_f64(&self) -> f64 {
+        #[cfg(feature = "bigint")]
+        if let Some(big) = &self.big {
+            // parsing the digits rounds correctly and saves pulling in num-traits
+            return big.to_string().parse().unwrap_or(f64::NAN);
+        }
+        return self.value as f64;
+    }
+    // both values, as long as neither one has grown past an i64
+    fn small(&self, other: &Integer) -> Option<(i64, i64)> {
+        if self.is_big() || other.is_big() {
+            return None;
+        }
+        return Some((self.value, other.value));
+    }
+
+    // arithmetic that returns None instead of overflowing
+    // with the bigint feature the result is promoted instead, so only dividing by zero is None
+    pub fn checked_add(&self, other: &Integer) -> Option<Integer> {
+        if let Some(sum) = self.small(other).and_then(|(a, b)| a.checked_add(b)) {
+            return Some(Integer::from_num(sum));
+        }
+        #[cfg(feature = "bigint")]
+        return Some(Integer::from_big(self.to_big() + other.to_big()));
+        #[cfg(not(feature = "bigint"))]
+        return None;
+    }
+    pub fn checked_sub(&self, other: &Integer) -> Option<Integer> {
+        if let Some(difference) = self.small(other).and_then(|(a, b)| a.checked_sub(b)) {
+            return Some(Integer::from_num(difference));
+        }
+        #[cfg(feature = "bigint")]
+        return Some(Integer::from_big(self.to_big() - other.to_big()));
+        #[cfg(not(feature = "bigint"))]
+        return None;
+    }
+    pub fn checked_mul(&self, other: &Integer) -> Option<Integer> {
+        if let Some(product) = self.small(other).and_then(|(a, b)| a.checked_mul(b)) {
+            return Some(Integer::from_num(product));
+        }
+        #[cfg(feature = "bigint")]
+        return Some(Integer::from_big(self.to_big() * other.to_big()));
+        #[cfg(not(feature = "bigint"))]
+        return None;
+    }
+    // also None when other is zero
+    pub fn checked_div(&self, other: &Integer) -> Option<Integer> {
+        if other.is_zero() {
+            return None;
+        }
+        if let Some(quotient) = self.small(other).and_then(|(a, b)| a.checked_div(b)) {
+            return Some(Integer::from_num(quotient));
+        }
+        #[cfg(feature = "bigint")]
+        return Some(Integer::from_big(self.to_big() / other.to_big()));
+        #[cfg(not(feature = "bigint"))]
+        return None;
+    }
+    pub fn checked_neg(&self) -> Option<Integer> {
+        if let Some(negated) = self.value.checked_neg().filter(|_| !self.is_big()) {
+            return Some(Integer::from_num(negated));
+        }
+        #[cfg(feature = "bigint")]
+        return Some(Integer::from_big(-self.to_big()));
+        #[cfg(not(feature = "bigint"))]
+        return None;
+    }
+}
+impl PartialOrd for Integer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        #[cfg(feature = "bigint")]
+        if self.is_big() || other.is_big() {
+            return self.to_big().partial_cmp(&other.to_big());
+        }
+        return self.value.partial_cmp(&other.value);
+    }
 }
 impl ObjectType for Integer {
     fn inspect(&self) -> String {
+        #[cfg(feature = "bigint")]
+        if let Some(big) = &self.big {
+            return big.to_string();
+        }
         return self.value.to_string();
     }
 }
 impl Display for Integer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Value in INTEGER: {}", &self.value)
+        write!(f, "Value in INTEGER: {}", self.inspect())
+    }
+}
+// a 64 bit IEEE float, so NaN is not equal to itself and dividing by zero gives inf or NaN
+#[derive(PartialEq, PartialOrd, Clone, Debug)]
+pub struct Float {
+    pub value: f64,
+}
+impl Default for Float {
+    fn default() -> Self {
+        Float {
+            value: 0.0,
+        }
+    }
+}
+impl Float {
+    pub fn new(value: f64) -> Float {
+        let mut float = Float::default();
+        float.value = value;
+        float
+    }
+}
+impl ObjectType for Float {
+    fn inspect(&self) -> String {
+        // the shortest text that parses back to the same value, always with a . or an exponent
+        // so it does not read as an integer, inf and NaN print as inf, -inf and NaN
+        return format!("{:?}", self.value);
+    }
+}
+impl Display for Float {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Value in FLOAT: {}", self.inspect())
     }
 }
 #[derive(PartialEq, Clone, Debug)]
@@ -150,6 +335,93 @@ impl Display for StringObject {
         write!(f, "{}", &self.value)
     }
 }
+#[derive(PartialEq, Clone, Debug)]
+pub struct Array {
+    pub elements: Vec<Object>,
+}
+impl Array {
+    pub fn new(elements: Vec<Object>) -> Array {
+        Array {
+            elements,
+        }
+    }
+}
+impl ObjectType for Array {
+    fn inspect(&self) -> String {
+        let elements: Vec<String> = self.elements.iter().map(|entry| entry.inspect()).collect();
+        return format!("[{}]", elements.join(", "));
+    }
+}
+impl Display for Array {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+// Key used to store an Object inside of a Hash
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
+pub enum HashKey {
+    Integer(i64),
+    #[cfg(feature = "bigint")]
+    BigInteger(BigInt),
+    Boolean(bool),
+    String(String),
+}
+pub trait Hashable {
+    fn hash_key(&self) -> HashKey;
+}
+impl Hashable for Integer {
+    fn hash_key(&self) -> HashKey {
+        // value is saturated for big integers, so they need a key of their own
+        #[cfg(feature = "bigint")]
+        if let Some(big) = &self.big {
+            return HashKey::BigInteger(big.clone());
+        }
+        return HashKey::Integer(self.value);
+    }
+}
+impl Hashable for Boolean {
+    fn hash_key(&self) -> HashKey {
+        return HashKey::Boolean(self.value);
+    }
+}
+impl Hashable for StringObject {
+    fn hash_key(&self) -> HashKey {
+        return HashKey::String(self.value.clone());
+    }
+}
+#[derive(PartialEq, Clone, Debug)]
+pub struct HashPair {
+    // keep the original key around so it can be shown again
+    pub key: Object,
+    pub value: Object,
+}
+#[derive(PartialEq, Clone, Debug)]
+pub struct Hash {
+    pub pairs: BTreeMap<HashKey, HashPair>,
+}
+impl Hash {
+    pub fn new() -> Hash {
+        Hash {
+            pairs: BTreeMap::new(),
+        }
+    }
+    pub fn get(&self, key: &HashKey) -> Option<Object> {
+        return self.pairs.get(key).map(|pair| pair.value.clone());
+    }
+}
+impl ObjectType for Hash {
+    fn inspect(&self) -> String {
+        let pairs: Vec<String> = self.pairs.values()
+            .map(|pair| format!("{}: {}", pair.key.inspect(), pair.value.inspect()))
+            .collect();
+        return format!("{{{}}}", pairs.join(", "));
+    }
+}
+impl Display for Hash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
 pub struct Null {}
 impl Default for Null {
     fn default() -> Self {
@@ -188,11 +460,32 @@ impl Display for Return {
     }
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(Clone)]
 pub struct Function {
     pub parameters: Parameters,
     pub body: BlockStatement,
-    pub environment: RefCell<Environment>,
+    // the environment the function was defined in, shared so the function sees
+    // bindings made after it, itself included
+    // a function stored in its own environment is a cycle, the evalulator clears the
+    // environments it made on reset and drop to break it
+    pub environment: Rc<RefCell<Environment>>,
+}
+// the environment can hold the function itself, so it is compared by pointer
+// and left out of the Debug output to keep both from recursing forever
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        self.parameters == other.parameters
+            && self.body == other.body
+            && Rc::ptr_eq(&self.environment, &other.environment)
+    }
+}
+impl std::fmt::Debug for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Function")
+            .field("parameters", &self.parameters)
+            .field("body", &self.body)
+            .finish_non_exhaustive()
+    }
 }
 impl ObjectType for Function {
     fn inspect(&self) -> String {
@@ -224,6 +517,38 @@ impl Display for Function {
         write!(f, "{}", params)
     }   
 }
+// native rust function callable from the language
+pub type BuiltinFunction = fn(Vec<Object>) -> Result<Object, EvalError>;
+
+#[derive(Clone, Debug)]
+pub struct Builtin {
+    pub name: String,
+    pub function: BuiltinFunction,
+}
+impl Builtin {
+    pub fn new(name: &str, function: BuiltinFunction) -> Builtin {
+        Builtin {
+            name: name.to_string(),
+            function,
+        }
+    }
+}
+// builtins are unique by name, comparing the function pointers is not reliable
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+impl ObjectType for Builtin {
+    fn inspect(&self) -> String {
+        return format!("builtin function: {}", self.name);
+    }
+}
+impl Display for Builtin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
 #[derive(PartialEq, Debug, Clone)]
 pub struct Environment {
     store: HashMap<String, Object>,
@@ -257,7 +582,7 @@ impl Environment {
                 return Some(object.clone())
             }
             None => match self.outer {
-                Some(ref outer) => outer.borrow_mut().get(name),
+                Some(ref outer) => outer.borrow().get(name),
                 None => None
             },
         }
@@ -266,6 +591,20 @@ impl Environment {
         Ok(self.store.insert(name, obj.clone()))
     }
 
+    // drop every binding and the outer scope, breaking any function cycle through this one
+    pub fn clear(&mut self) -> () {
+        self.store.clear();
+        self.outer = None;
+    }
+    // the bindings of this scope sorted by name
+    pub fn bindings(&self) -> Vec<(String, Object)> {
+        let mut bindings: Vec<(String, Object)> = self.store.iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        bindings.sort_by(|a, b| a.0.cmp(&b.0));
+        return bindings;
+    }
+
     pub fn print(&self) -> () {
         if self.store.is_empty() { 
            //println!("Empty store");
@@ -1,44 +1,127 @@
 use std::fmt::Display;
 
-use super::token::{Token, Lexer};
+use super::token::{Token, Lexer, Span};
 use super::ast::{*, self};
+use super::object::Integer;
 
-#[derive(Debug)]
-pub enum ParseError{
-    FailedIdent(Token),
-    InvalidToken(Token),
-    InvalidInfixOp(Token),
-    InvalidPrecConversion(Token),
-    InvalidPrefixOp(Token),
-    InvalidStatementToken(Token),
+#[derive(Debug, Clone, PartialEq)]
+pub enum Severity {
+    Error,
+    // reported, but the program still runs
+    Warning,
 }
-impl Display for ParseError {
+impl Display for Severity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ParseError::InvalidToken(token) => write!(f, "InvalidToken: {}", token),
-            ParseError::InvalidPrecConversion(token) => write!(f, "InvalidPrecConversion: {}", token),
-            ParseError::InvalidPrefixOp(token) => write!(f, "InvalidPrefixOp: {}", token),
-            ParseError::InvalidInfixOp(token) => write!(f, "InvalidInfixOp: {}", token),
-            ParseError::FailedIdent(token) => write!(f, "Failed to read ident: token: {}", token),
-            ParseError::InvalidStatementToken(token) => write!(f, "Failed to read Statement token: {}", token),
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    FailedIdent,
+    InvalidToken,
+    InvalidInfixOp,
+    InvalidPrecConversion,
+    InvalidPrefixOp,
+    InvalidStatementToken,
+    // the same name twice in one parameter list
+    DuplicateParameter(String),
+    // an integer literal too big for an Integer
+    IntegerOutOfRange,
+    // a float literal that rounds to infinity
+    FloatOutOfRange,
+    // message from the lexer
+    Lexical(String),
+    // a statement after a return in the same block, it never runs
+    UnreachableCode,
+}
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseErrorKind::InvalidToken => write!(f, "InvalidToken"),
+            ParseErrorKind::InvalidPrecConversion => write!(f, "InvalidPrecConversion"),
+            ParseErrorKind::InvalidPrefixOp => write!(f, "InvalidPrefixOp"),
+            ParseErrorKind::InvalidInfixOp => write!(f, "InvalidInfixOp"),
+            ParseErrorKind::FailedIdent => write!(f, "Failed to read ident"),
+            ParseErrorKind::InvalidStatementToken => write!(f, "Failed to read Statement token"),
+            ParseErrorKind::DuplicateParameter(name) => write!(f, "Duplicate parameter {}", name),
+            ParseErrorKind::IntegerOutOfRange => write!(f, "Integer literal out of range"),
+            ParseErrorKind::FloatOutOfRange => write!(f, "Float literal out of range"),
+            ParseErrorKind::Lexical(message) => write!(f, "{}", message),
+            ParseErrorKind::UnreachableCode => write!(f, "Unreachable code after return"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    // description of what the parser wanted at this point
+    pub expected: String,
+    pub found: Token,
+    pub span: Span,
+    pub severity: Severity,
+}
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, expected: &str, found: Token, span: Span) -> ParseError {
+        ParseError {
+            kind,
+            expected: expected.to_string(),
+            found,
+            span,
+            severity: Severity::Error,
         }
     }
+    pub fn warning(kind: ParseErrorKind, expected: &str, found: Token, span: Span) -> ParseError {
+        let mut warning = ParseError::new(kind, expected, found, span);
+        warning.severity = Severity::Warning;
+        return warning;
+    }
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ParseErrorKind::Lexical(message) => write!(f, "{}: {}: {}",
+                self.span.start, self.severity, message),
+            kind => write!(f, "{}: {}: {}: expected {}, found {}",
+                self.span.start, self.severity, kind, self.expected, self.found),
+        }
+    }
+}
+
+// everything parse found: the statements it could build and every error along the way
+pub struct ParseResult {
+    pub program: Program,
+    pub errors: Vec<ParseError>,
+}
+impl ParseResult {
+    pub fn has_errors(&self) -> bool {
+        return self.errors.iter().any(|error| error.severity == Severity::Error);
+    }
 }
 #[derive(PartialEq, PartialOrd)]
 pub enum Prec {
     LOWEST,
+    LOGICALOR,      // ||
+    LOGICALAND,     // &&
     COMPARES,         // ==
-    LESSGREATER,    // > or <
+    LESSGREATER,    // > or < or >= or <=
     SUM,            // +
     PRODUCT,        // *
     PREFIX,         // -X or !X
     LPAREN,
     CALL,           // myFunction(X)
+    INDEX,          // array[index]
 }
 impl Display for Prec {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Prec::LOWEST => write!(f, "LOWEST"),
+            Prec::LOGICALOR => write!(f, "LOGICALOR"),
+            Prec::LOGICALAND => write!(f, "LOGICALAND"),
             Prec::COMPARES => write!(f, "COMPARES"),
             Prec::LESSGREATER => write!(f, "LESSGREATER"),
             Prec::SUM => write!(f, "SUM"),
@@ -46,17 +129,22 @@ impl Display for Prec {
             Prec::PREFIX => write!(f, "PREFIX"),
             Prec::LPAREN => write!(f, "LPAREN"),
             Prec::CALL => write!(f, "CALL"),
+            Prec::INDEX => write!(f, "INDEX"),
         }
     }
 }
-pub fn token_to_prec_map(token: Token) -> Result<Prec, ParseError> {
+// span is where token sits, for the error when it is not an operator
+pub fn token_to_prec_map(token: Token, span: Span) -> Result<Prec, ParseError> {
     let prec_value = match token {
+        Token::OR => Prec::LOGICALOR,
+        Token::AND => Prec::LOGICALAND,
         Token::EQUAL | Token::NEQUAL => Prec::COMPARES,
-        Token::LTHAN | Token::GTHAN => Prec::LESSGREATER,
+        Token::LTHAN | Token::GTHAN | Token::LEQUAL | Token::GEQUAL => Prec::LESSGREATER,
         Token::PLUS | Token::SUBTRACT => Prec::SUM,
         Token::FSLASH | Token::STAR => Prec::PRODUCT,
         Token::LPAREN => Prec::LPAREN,
-        toke => return Err(ParseError::InvalidPrecConversion(toke)), 
+        Token::LBRACKET => Prec::INDEX,
+        toke => return Err(ParseError::new(ParseErrorKind::InvalidPrecConversion, "an operator", toke, span)),
     };
     Ok(prec_value)
 }
@@ -72,15 +160,22 @@ pub struct Parser {
     lex: Lexer,
     cur_token: Token,
     peek_token: Token,
-    errors: Vec<String>,
+    cur_span: Span,
+    peek_span: Span,
+    // how deep in braces cur_token is, used to recover from errors
+    brace_depth: usize,
+    errors: Vec<ParseError>,
 }
 impl Default for Parser {
     fn default() -> Self {
         Parser {
             lex: Lexer::default(),
-            cur_token: Token::ILLEGAL,
-            peek_token: Token::ILLEGAL,
-            errors: Vec::<String>::new(),
+            cur_token: Token::ILLEGAL(String::new()),
+            peek_token: Token::ILLEGAL(String::new()),
+            cur_span: Span::default(),
+            peek_span: Span::default(),
+            brace_depth: 0,
+            errors: Vec::<ParseError>::new(),
         }       
     }
 }
@@ -96,16 +191,50 @@ impl Parser {
         return parser;
     }
     fn next_token(&mut self) {
+        // a { and its matching } sit at the same depth, everything between is one deeper
+        if self.cur_token_is(Token::LBRACE) {
+            self.brace_depth += 1;
+        }
         self.cur_token = self.peek_token.clone();
-        self.peek_token = self.lex.next_token().unwrap();
+        self.cur_span = self.peek_span;
+        if self.cur_token_is(Token::RBRACE) {
+            self.brace_depth = self.brace_depth.saturating_sub(1);
+        }
+        let mut next = self.lex.next_spanned_token().unwrap();
+        // comments kept by the lexer mean nothing to the parser
+        while let Token::COMMENT(_) = next.token {
+            next = self.lex.next_spanned_token().unwrap();
+        }
+        self.peek_token = next.token;
+        self.peek_span = next.span;
+        // surface anything the lexer complained about as a parse error
+        for diagnostic in self.lex.diagnostics.drain(..) {
+            self.errors.push(ParseError::new(
+                ParseErrorKind::Lexical(diagnostic.message),
+                "",
+                self.peek_token.clone(),
+                diagnostic.span,
+            ));
+        }
     }
-    fn errors(&self) -> &Vec<String> {
+    pub fn errors(&self) -> &Vec<ParseError> {
         return &self.errors;
     }
-    fn peek_error(&mut self, token: &Token) {
-        let message = format!("Expected next token to be: {}, instead found: {}",
-            token.clone(), self.peek_token);
-        self.errors.push(message);
+    // error about the token we are sitting on
+    fn cur_error(&self, kind: ParseErrorKind, expected: &str) -> ParseError {
+        return ParseError::new(kind, expected, self.cur_token.clone(), self.cur_span);
+    }
+    // error about the token we expected to come next
+    fn peek_error(&self, kind: ParseErrorKind, expected: &str) -> ParseError {
+        return ParseError::new(kind, expected, self.peek_token.clone(), self.peek_span);
+    }
+    pub fn parse(&mut self) -> ParseResult {
+        // parse the whole input and hand back the program with every error found
+        let program = self.parse_program();
+        return ParseResult {
+            program,
+            errors: std::mem::take(&mut self.errors),
+        };
     }
     pub fn parse_program(&mut self) -> ast::Program {
         // create a new program which is just an empty vector of statements
@@ -113,16 +242,57 @@ impl Parser {
         // iterate through the tokens until EOF
         while !self.cur_token_is(Token::EOF) {
             // if the statement is ok then push it to program and move forward one
+            let level = self.brace_depth;
+            let (found, start) = (self.cur_token.clone(), self.cur_span);
             match self.parse_statement() { 
-                Ok(statement) => program.statements.push(statement),
+                Ok(statement) => {
+                    self.check_reachable(&program.statements, found, start);
+                    program.statements.push(statement);
+                },
                 Err(error) =>{
-                    println!("error is: {}", error);
+                    // report it then skip the rest of the broken statement
+                    self.record_error(error);
+                    self.synchronize(level);
                 }
             };
             self.next_token();
         }
         return program;
     }
+    // warn about the first statement after a return, found and start are where it begins
+    fn check_reachable(&mut self, statements: &[Statement], found: Token, start: Span) {
+        if let Some(Statement::ReturnStatement(_)) = statements.last() {
+            self.errors.push(ParseError::warning(ParseErrorKind::UnreachableCode, "the end of the block", found, start));
+        }
+    }
+    fn record_error(&mut self, error: ParseError) {
+        // ILLEGAL tokens were already reported by the lexer, dont report them twice
+        if let Token::ILLEGAL(_) = error.found {
+            return;
+        }
+        self.errors.push(error);
+    }
+    fn synchronize(&mut self, level: usize) {
+        // panic mode recovery: skip tokens until cur_token ends the broken statement
+        // (a ; or the } closing the block) or peek_token starts a new one (let, return, fn).
+        // level is the brace depth the statement started at, so braces opened
+        // while skipping are skipped as a whole
+        loop {
+            match self.cur_token {
+                Token::EOF => return,
+                Token::RBRACE if self.brace_depth < level => return,
+                Token::SEMICOLON if self.brace_depth == level => return,
+                _ => {},
+            }
+            if self.brace_depth == level {
+                match self.peek_token {
+                    Token::LET | Token::RETURN | Token::FUNCTION | Token::RBRACE | Token::EOF => return,
+                    _ => {},
+                }
+            }
+            self.next_token();
+        }
+    }
     fn parse_statement(&mut self) -> Result<Statement, ParseError> {
         // match with Let statement or ignore for now
         match &self.cur_token {
@@ -132,6 +302,7 @@ impl Parser {
         }
     }
     fn parse_let_statement(&mut self) -> Result<Statement, ParseError> {
+        let start = self.cur_span;
         // advance token passed the Token::LET
         self.next_token();
         //read ident for LetStatement.name
@@ -152,24 +323,26 @@ impl Parser {
                 LetStatement {
                     name: ident_name,
                     value: expression,
+                    span: start.to(self.cur_span),
                 }
             )
         )
     }
     fn parse_return_statement(&mut self) -> Result<Statement, ParseError> {
+        let start = self.cur_span;
         // move passed the return token
         self.next_token();
         let expression = self.parse_expression(Prec::LOWEST)?;
-        while !self.peek_token_is(&Token::SEMICOLON) {
+        // move passed final semicolon
+        if self.peek_token_is(&Token::SEMICOLON) {
             self.next_token();
         }
-        // move passed final semicolon
-        self.next_token();
 
         Ok(
             Statement::ReturnStatement(
                 ReturnStatement {
                     ret_value: expression,
+                    span: start.to(self.cur_span),
                 }
             )
         )
@@ -186,64 +359,122 @@ impl Parser {
     fn parse_statement_ident(&mut self) -> Result<Expression, ParseError> {
         // read ident then create the expression for Expression::Ident
         let expression = match self.read_ident() {
-            Ok(ident) => Ok(Expression::Ident(ident)),
-            _ => Err(ParseError::FailedIdent(self.cur_token.clone())),
+            Ok(ident) => Ok(Expression::Ident(ident, self.cur_span)),
+            _ => Err(self.cur_error(ParseErrorKind::FailedIdent, "an identifier")),
         };
         return expression;
     }
     fn parse_int(&mut self) -> Result<Expression, ParseError> { 
         let expression = match &self.cur_token {
-            Token::INT(num) => Ok(Expression::Literal(Literal::Int(num.clone()))),
-            _ => Err(ParseError::InvalidToken(self.cur_token.clone())),
+            Token::INT(num) if Integer::parse(num).is_none() => Err(self.cur_error(ParseErrorKind::IntegerOutOfRange, "an integer that fits in 64 bits")),
+            Token::INT(num) => Ok(Expression::Literal(Literal::Int(num.clone()), self.cur_span)),
+            _ => Err(self.cur_error(ParseErrorKind::InvalidToken, "an integer")),
+        };
+        return expression;
+    }
+    fn parse_float(&mut self) -> Result<Expression, ParseError> {
+        let expression = match &self.cur_token {
+            Token::FLOAT(num) if !num.parse::<f64>().is_ok_and(f64::is_finite) => Err(self.cur_error(ParseErrorKind::FloatOutOfRange, "a finite float")),
+            Token::FLOAT(num) => Ok(Expression::Literal(Literal::Float(num.clone()), self.cur_span)),
+            _ => Err(self.cur_error(ParseErrorKind::InvalidToken, "a float")),
         };
         return expression;
     }
     fn parse_boolean(&mut self) -> Result<Expression, ParseError> { 
         let expression = match &self.cur_token {
-            Token::TRUE => Ok(Expression::Literal(Literal::Bool(true))),
-            Token::FALSE => Ok(Expression::Literal(Literal::Bool(false))),
-            _ => Err(ParseError::InvalidToken(self.cur_token.clone())),
+            Token::TRUE => Ok(Expression::Literal(Literal::Bool(true), self.cur_span)),
+            Token::FALSE => Ok(Expression::Literal(Literal::Bool(false), self.cur_span)),
+            _ => Err(self.cur_error(ParseErrorKind::InvalidToken, "a boolean")),
         };
         return expression;
     }
     fn parse_grouped_expression(&mut self) -> Result<Expression, ParseError> {
         self.next_token();
         let expression = self.parse_expression(Prec::LOWEST);
+        // expect_peek moves onto the right parenthesis, the caller moves passed it
         self.expect_peek(Token::RPAREN)?;
-        // DONT FORGET TO MOVE PASSED THE RIGHT PARENTHESIS
-        if self.peek_token_is(&Token::RPAREN) {
-            self.next_token();
-        }
         return expression;
     }
     fn parse_block_statement(&mut self) -> Result<Statement, ParseError> {
         let mut block_statement = BlockStatement::default();
+        let start = self.cur_span;
         // skip the left brace
         self.next_token();
 
         while !self.cur_token_is(Token::RBRACE) && !self.cur_token_is(Token::EOF) {
-
-            let statement = self.parse_statement()?;
-            block_statement.statements.push(statement);
+            let level = self.brace_depth;
+            let (found, start) = (self.cur_token.clone(), self.cur_span);
+            match self.parse_statement() {
+                Ok(statement) => {
+                    self.check_reachable(&block_statement.statements, found, start);
+                    block_statement.statements.push(statement);
+                },
+                Err(error) => {
+                    // recover inside the block so one mistake doesnt lose the whole body
+                    self.record_error(error);
+                    self.synchronize(level);
+                    if self.cur_token_is(Token::RBRACE) {
+                        continue;
+                    }
+                }
+            }
             self.next_token();
         }
+        block_statement.span = start.to(self.cur_span);
         return Ok(
             Statement::BlockStatement(
                 block_statement
             )
         )
     }
+    fn parse_interpolated_string(&mut self) -> Result<Expression, ParseError> {
+        // input: "a ${x} b ${y + 1} c"
+        // INTERPSTART(a ) x INTERPMID( b ) (y + 1) INTERPEND( c)
+        let start = self.cur_span;
+        let mut parts = Vec::<InterpolationPart>::new();
+        loop {
+            // sitting on the text before a ${
+            if let Token::INTERPSTART(text) | Token::INTERPMID(text) = &self.cur_token {
+                if !text.is_empty() {
+                    parts.push(InterpolationPart::Literal(text.clone(), self.cur_span));
+                }
+            }
+            self.next_token();
+            parts.push(InterpolationPart::Expression(self.parse_expression(Prec::LOWEST)?));
+            match &self.peek_token {
+                Token::INTERPMID(_) => self.next_token(),
+                Token::INTERPEND(text) => {
+                    if !text.is_empty() {
+                        parts.push(InterpolationPart::Literal(text.clone(), self.peek_span));
+                    }
+                    self.next_token();
+                    break;
+                },
+                _ => return Err(self.peek_error(ParseErrorKind::InvalidToken, "} to close the interpolation")),
+            }
+        }
+        return Ok(
+            Expression::InterpolatedString(
+                InterpolatedString {
+                    parts,
+                    span: start.to(self.cur_span),
+                }
+            )
+        );
+    }
     fn parse_string_literal(&mut self) -> Result<Expression, ParseError> {
         let output = self.cur_token.clone().to_string();
         return Ok(
             Expression::Literal(
-                Literal::String(output)
+                Literal::String(output),
+                self.cur_span,
             ))
     }
     fn parse_expression_if(&mut self) -> Result<Expression, ParseError> {
         // if (x>y) {x}
         // if (x>y) {x} else {y}
         let mut alternative = None;
+        let start = self.cur_span;
         // peek token better be an LPAREN or ill freak out 
         self.expect_peek(Token::LPAREN)?;
         // skip the LPAREN
@@ -256,7 +487,7 @@ impl Parser {
         let consequence = if let Statement::BlockStatement(block_statement) = self.parse_block_statement()? {
             block_statement
         } else {
-            return Err(ParseError::InvalidStatementToken(self.cur_token.clone()))
+            return Err(self.cur_error(ParseErrorKind::InvalidStatementToken, "a block statement"))
         };
 
         if self.peek_token_is(&Token::ELSE) {
@@ -268,7 +499,7 @@ impl Parser {
             alternative = if let Statement::BlockStatement(block_statement) = self.parse_block_statement()? {
                 Some(block_statement)
             } else {
-                return Err(ParseError::InvalidStatementToken(self.cur_token.clone()))
+                return Err(self.cur_error(ParseErrorKind::InvalidStatementToken, "a block statement"))
             }
         }
         return Ok( 
@@ -277,30 +508,38 @@ impl Parser {
                         condition, 
                         consequence,
                         alternative,
+                        span: start.to(self.cur_span),
                     })
             )
 
     }
     fn parse_function_parameters(&mut self) -> Result<Parameters, ParseError> {
         // input: FN(), FN(x), FN(x,y)
+        // only plain identifiers separated by commas, each name once
         let mut variables = Vec::<Expression>::new();
         if self.peek_token_is(&Token::RPAREN) {
             self.next_token();
             return Ok(Parameters { variables } );
         }
-        // skip the LPAREN
-        self.next_token();
-
-        // stop if RPAREN, checks for single variable 
-        while !self.cur_token_is(Token::RPAREN) && !self.cur_token_is(Token::EOF) {
-            // if multiple variables, check for comma before parsing 
-            if self.cur_token_is(Token::COMMA) {
-                self.next_token();
+        loop {
+            // skip the LPAREN or COMMA
+            self.next_token();
+            let ident = self.read_ident()
+                .map_err(|_| self.cur_error(ParseErrorKind::FailedIdent, "a parameter name"))?;
+            let duplicate = variables.iter().any(|variable| match variable {
+                Expression::Ident(name, _) => *name == ident,
+                _ => false,
+            });
+            if duplicate {
+                return Err(self.cur_error(ParseErrorKind::DuplicateParameter(ident), "a parameter name not used before"));
+            }
+            variables.push(Expression::Ident(ident, self.cur_span));
+            if !self.peek_token_is(&Token::COMMA) {
+                break;
             }
-            let idents = self.parse_statement_ident()?;
-            variables.push(idents);
             self.next_token();
         }
+        self.expect_peek(Token::RPAREN)?;
         return Ok(
                 Parameters{
                     variables,
@@ -310,6 +549,7 @@ impl Parser {
     }
     fn parse_expression_function(&mut self) -> Result<Expression, ParseError> {
         //input FN (...) {...}
+        let start = self.cur_span;
         //check for LPAREN then skip it
         self.expect_peek(Token::LPAREN)?;
         //IF there are parameters assign them to parameters else NONE
@@ -319,7 +559,7 @@ impl Parser {
         let body = if let Statement::BlockStatement(block_statement) = self.parse_block_statement()? {
             block_statement
         } else {
-            return Err(ParseError::InvalidStatementToken(self.cur_token.clone()));
+            return Err(self.cur_error(ParseErrorKind::InvalidStatementToken, "a block statement"));
         };
 
         return Ok(
@@ -327,6 +567,7 @@ impl Parser {
                 FunctionLiteral {
                     parameters,
                     body,
+                    span: start.to(self.cur_span),
                 }
             )
         )
@@ -336,8 +577,9 @@ impl Parser {
         let prefix_op = match self.cur_token.clone() {
             Token::BANG => PrefixOp::BANG,
             Token::SUBTRACT => PrefixOp::NEGATIVE,
-            _ => return Err(ParseError::InvalidToken(self.cur_token.clone())),
+            _ => return Err(self.cur_error(ParseErrorKind::InvalidToken, "a prefix operator")),
         };
+        let start = self.cur_span;
         // move token forward one from the prefix operator
         self.next_token();
 
@@ -347,7 +589,8 @@ impl Parser {
                 Expression::PrefixExpression(
                     PrefixExpression {
                         prefix_op,
-                        expression: Box::new(expression)
+                        span: start.to(expression.span()),
+                        expression: Box::new(expression),
                     }
                 )
             ),
@@ -365,9 +608,13 @@ impl Parser {
             Token::FSLASH => InfixOp::DIVIDE,
             Token::LTHAN => InfixOp::LESSTHAN,
             Token::GTHAN => InfixOp::GREATERTHAN,
+            Token::LEQUAL => InfixOp::LESSEQUAL,
+            Token::GEQUAL => InfixOp::GREATEREQUAL,
             Token::EQUAL => InfixOp::COMPARE,
             Token::NEQUAL => InfixOp::NEQUALS,
-            _ => return Err(ParseError::InvalidToken(self.cur_token.clone())),
+            Token::AND => InfixOp::AND,
+            Token::OR => InfixOp::OR,
+            _ => return Err(self.cur_error(ParseErrorKind::InvalidToken, "an infix operator")),
         };
 
         let prec = self.cur_prec_is();
@@ -381,40 +628,127 @@ impl Parser {
             Ok(expression) => Ok(
                 Expression::InfixExpression(
                     InfixExpression {
+                        span: express.span().to(expression.span()),
                         left_expression: Box::new(express),
                         infix_op,
-                        right_expression: Box::new(expression)
+                        right_expression: Box::new(expression),
                     }
                 )
             ),
-            Err(_) => Err(ParseError::InvalidInfixOp(self.cur_token.clone())),
+            Err(error) => Err(error),
         };
         return expression;
     }
 
+    fn parse_expression_list(&mut self, end: Token) -> Result<Vec<Expression>, ParseError> {
+        // cur_token is the opening ( or [
+        // input: ), x), x + y, x * y)  or  ], 1], 1, 2 * 3]
+        let mut list = Vec::<Expression>::new();
+        //check for an empty list, move onto the end token
+        if self.peek_token_is(&end) {
+            self.next_token();
+            return Ok(list);
+        }
+        // skip the opening token
+        self.next_token();
+        list.push(self.parse_expression(Prec::LOWEST)?);
+        // skip the comma and parse the next entry
+        while self.peek_token_is(&Token::COMMA) {
+            self.next_token();
+            self.next_token();
+            list.push(self.parse_expression(Prec::LOWEST)?);
+        }
+        // finish on the end token
+        self.expect_peek(end)?;
+        return Ok(list);
+    }
     fn parse_arguments(&mut self) -> Result<Arguments, ParseError> {
         // input (); , (x); , (x + y); , (x, x + y, x * y);
-        let mut variables = Vec::<Expression>::new();
-        //check for zero args
-        if self.peek_token_is(&Token::RPAREN) {
-            return Ok(Arguments {variables} );
+        let variables = self.parse_expression_list(Token::RPAREN)?;
+        return Ok(
+                Arguments { variables }
+               );
+    }
+    fn parse_array_literal(&mut self) -> Result<Expression, ParseError> {
+        // input: [], [1], [1, 2 * 3, fn(x) { x }]
+        let start = self.cur_span;
+        let elements = self.parse_expression_list(Token::RBRACKET)?;
+        return Ok(
+            Expression::ArrayLiteral(
+                ArrayLiteral {
+                    elements,
+                    span: start.to(self.cur_span),
+                }
+            )
+        );
+    }
+    fn parse_hash_literal(&mut self) -> Result<Expression, ParseError> {
+        // input: {}, {"one": 1}, {"one": 1, 2: true, "three": 1 + 2}
+        let start = self.cur_span;
+        let mut pairs = Vec::<(Expression, Expression)>::new();
+        while !self.peek_token_is(&Token::RBRACE) {
+            // skip the LBRACE or the COMMA
+            self.next_token();
+            let key = self.parse_expression(Prec::LOWEST)?;
+            self.expect_peek(Token::COLON)?;
+            self.next_token();
+            let value = self.parse_expression(Prec::LOWEST)?;
+            pairs.push((key, value));
+            // every pair but the last needs a comma after it
+            if !self.peek_token_is(&Token::RBRACE) {
+                self.expect_peek(Token::COMMA)?;
+            }
         }
-        //skip LPAREN
+        self.expect_peek(Token::RBRACE)?;
+        return Ok(
+            Expression::HashLiteral(
+                HashLiteral {
+                    pairs,
+                    span: start.to(self.cur_span),
+                }
+            )
+        );
+    }
+    fn parse_expression_index(&mut self, left: Expression) -> Result<Expression, ParseError> {
+        // input: array[1], array[1 + 1], [1, 2][0]
+        // or a slice: string[1:3], string[:3], string[1:], string[:]
+        // skip the LBRACKET
         self.next_token();
-
-        while !self.cur_token_is(Token::RPAREN) & !self.cur_token_is(Token::SEMICOLON){
-            // x, x + y, x * y);
-            // if comma, skip comma
-            if self.cur_token_is(Token::COMMA) {
-                self.next_token();
+        let mut start = None;
+        if !self.cur_token_is(Token::COLON) {
+            let index = self.parse_expression(Prec::LOWEST)?;
+            if !self.peek_token_is(&Token::COLON) {
+                self.expect_peek(Token::RBRACKET)?;
+                return Ok(
+                    Expression::IndexExpression(
+                        IndexExpression {
+                            span: left.span().to(self.cur_span),
+                            left: Box::new(left),
+                            index: Box::new(index),
+                        }
+                    )
+                );
             }
-            let arg = self.parse_expression(Prec::LOWEST)?;
-            variables.push(arg);
+            start = Some(Box::new(index));
+            self.next_token();
+        }
+        // sitting on the colon
+        let mut end = None;
+        if !self.peek_token_is(&Token::RBRACKET) {
             self.next_token();
+            end = Some(Box::new(self.parse_expression(Prec::LOWEST)?));
         }
+        self.expect_peek(Token::RBRACKET)?;
         return Ok(
-                Arguments { variables }
-               );
+            Expression::SliceExpression(
+                SliceExpression {
+                    span: left.span().to(self.cur_span),
+                    left: Box::new(left),
+                    start,
+                    end,
+                }
+            )
+        );
     }
     fn parse_expression_call(&mut self, expression: Expression) -> Result<Expression, ParseError> {
         // input: FN(), FN(x), FN(x,y)
@@ -422,6 +756,7 @@ impl Parser {
        return Ok(
             Expression::CallExpression(
                 CallExpression {
+                    span: expression.span().to(self.cur_span),
                     function: Box::new(expression), 
                     arguments,
                 }
@@ -434,12 +769,16 @@ impl Parser {
             match self.cur_token {
                 Token::IDENT(_) => Parser::parse_statement_ident,
                 Token::INT(_) => Parser::parse_int,
+                Token::FLOAT(_) => Parser::parse_float,
                 Token::BANG | Token::SUBTRACT => Parser::parse_expression_prefix, 
                 Token::TRUE | Token::FALSE => Parser::parse_boolean,
                 Token::LPAREN => Parser::parse_grouped_expression,
                 Token::IF => Parser::parse_expression_if,
                 Token::FUNCTION => Parser::parse_expression_function,
                 Token::STRING(_) => Parser::parse_string_literal,
+                Token::INTERPSTART(_) => Parser::parse_interpolated_string,
+                Token::LBRACKET => Parser::parse_array_literal,
+                Token::LBRACE => Parser::parse_hash_literal,
                 _ => return None,
             }
         )
@@ -456,9 +795,14 @@ impl Parser {
                 | Token::FSLASH
                 | Token::GTHAN
                 | Token::LTHAN
+                | Token::GEQUAL
+                | Token::LEQUAL
                 | Token::EQUAL
-                | Token::NEQUAL => Parser::parse_expression_infix,
+                | Token::NEQUAL
+                | Token::AND
+                | Token::OR => Parser::parse_expression_infix,
                 Token::LPAREN => Parser::parse_expression_call,
+                Token::LBRACKET => Parser::parse_expression_index,
                 _ => return None,
             }
         )
@@ -470,14 +814,14 @@ impl Parser {
         //  input = "!true"     input = "5 * 5"       map that to an InfixOp (still peek_token) 
         //  input = "-5"        input = "5 == 5"      get rest of expression
         //  get the prefix if any, or return the literals as an expression 
-        let prefix = self.prefix_parser().ok_or_else(|| ParseError::InvalidPrefixOp(self.cur_token.clone()))?;
+        let prefix = self.prefix_parser().ok_or_else(|| self.cur_error(ParseErrorKind::InvalidPrefixOp, "an expression"))?;
 
         let mut left_exp = prefix(self)?;
 
         // if next token is a semicolon just skip and return left_exp i.e. "++i;" there is no Infix
         // else: 1 + 2; 1 is prefix, we check peek: {+}, so parse the infix and right exp
         while !self.peek_token_is(&Token::SEMICOLON) && prec < self.peek_prec_is() {
-            let infix = self.infix_parser().ok_or_else(|| ParseError::InvalidInfixOp(self.cur_token.clone()))?;
+            let infix = self.infix_parser().ok_or_else(|| self.peek_error(ParseErrorKind::InvalidInfixOp, "an infix operator"))?;
             self.next_token();
             left_exp = infix(self, left_exp)?;
         };
@@ -490,7 +834,7 @@ impl Parser {
         // match token 
         match &self.cur_token {
             Token::IDENT(string) => Ok(string.clone()),
-            _ => Err(ParseError::FailedIdent(self.cur_token.clone()))
+            _ => Err(self.cur_error(ParseErrorKind::FailedIdent, "an identifier"))
         }
     }
     fn cur_token_is(&self, t: Token) -> bool {
@@ -501,7 +845,7 @@ impl Parser {
         }
     }
     fn cur_prec_is(&self) -> Prec {
-        if let Ok(prec) = token_to_prec_map(self.cur_token.clone()) {
+        if let Ok(prec) = token_to_prec_map(self.cur_token.clone(), self.cur_span) {
             return prec;
         } else {
             return Prec::LOWEST;
@@ -515,7 +859,7 @@ impl Parser {
         }
     }
     fn peek_prec_is(&self) -> Prec {
-        if let Ok(prec) = token_to_prec_map(self.peek_token.clone()) {
+        if let Ok(prec) = token_to_prec_map(self.peek_token.clone(), self.peek_span) {
             return prec;
         } else {
             return Prec::LOWEST;
@@ -526,8 +870,7 @@ impl Parser {
             self.next_token();
             return Ok(());
         } else {
-            self.peek_error(&t);
-            return Err(ParseError::InvalidToken(self.peek_token.clone()));
+            return Err(self.peek_error(ParseErrorKind::InvalidToken, &t.to_string()));
         }
     }
     pub fn check_parsing_errors(&self) -> () {
@@ -556,12 +899,16 @@ impl Parser {
 
 
 #[cfg(test)]
+// the tests build their tables with vec! and index them by position
+#[allow(clippy::useless_vec, clippy::needless_range_loop, clippy::unnecessary_cast)]
 mod test{
     use anyhow::Result;
     use crate::token::ast::{LetStatement,Statement,Program,Expression};
 
+    use crate::token::token::Span;
     use super::Lexer;
-    use super::Parser;
+    use super::{Parser, ParseErrorKind, Severity, token_to_prec_map};
+    use crate::token::token::Token;
 
     #[test]
     fn test_let_statements() -> Result<(), String> {
@@ -674,7 +1021,8 @@ mod test{
         let statement =  Statement::LetStatement(
                                 LetStatement {
                                     name: "myVar".to_string(),
-                                    value: Expression::Ident("anotherVar".to_string()),
+                                    value: Expression::Ident("anotherVar".to_string(), Span::default()),
+                                    span: Span::default(),
                                 }
                             );
         let program = Program{
@@ -733,6 +1081,8 @@ mod test{
             5 == 5;
             5 != 5;
             !true;
+            5 >= 5;
+            5 <= 5;
         ".to_string();
         
         let values = vec![
@@ -744,7 +1094,9 @@ mod test{
           "(5 < 5)",
           "(5 == 5)",
           "(5 != 5)",
-          "(! true)"
+          "(! true)",
+          "(5 >= 5)",
+          "(5 <= 5)",
         ];
         
         let l = Lexer::new(input);
@@ -752,8 +1104,8 @@ mod test{
         let program = p.parse_program();
         check_parsing_errors(p);
         
-        if program.statements.len() != 9 {
-            return Err(format!("Program statements does not contain nine statements, got: {}", program.statements.len()));
+        if program.statements.len() != 11 {
+            return Err(format!("Program statements does not contain eleven statements, got: {}", program.statements.len()));
         }
 
         for i in 0..values.len() {
@@ -780,6 +1132,14 @@ mod test{
             3 < 5 == true;
             1 + (2 + 3) * 4;
             -(5 + 5);
+            a + 1 <= b * 2 == true;
+            a < b && c > d || e;
+            a || b && c;
+            !a && b == c;
+            a * [1, 2, 3, 4][b * c] * d;
+            add(a * b[2], b[1], 2 * [1, 2][1]);
+            s[a + 1:b * 2] + t[:1];
+            s[i:][1];
         ".to_string();
         
         let values = vec![
@@ -795,6 +1155,14 @@ mod test{
           "((3 < 5) == true)",
           "(1 + ((2 + 3) * 4))",
           "(- (5 + 5))",
+          "(((a + 1) <= (b * 2)) == true)",
+          "(((a < b) && (c > d)) || e)",
+          "(a || (b && c))",
+          "((! a) && (b == c))",
+          "((a * ([1, 2, 3, 4][(b * c)])) * d)",
+          "FN (add) {(a * (b[2])),(b[1]),(2 * ([1, 2][1]))}",
+          "((s[(a + 1):(b * 2)]) + (t[:1]))",
+          "((s[i:])[1])",
         ];
         
         let l = Lexer::new(input);
@@ -802,8 +1170,8 @@ mod test{
         let program = p.parse_program();
         check_parsing_errors(p);
         
-        if program.statements.len() != 12 {
-            return Err(format!("Program statements does not contain 12 statements, got: {}", program.statements.len()));
+        if program.statements.len() != 20 {
+            return Err(format!("Program statements does not contain 20 statements, got: {}", program.statements.len()));
         }
 
         for i in 0..values.len() {
@@ -814,6 +1182,33 @@ mod test{
         Ok(())
     }
     
+    #[test]
+    fn test_grouped_expressions() -> Result<(), String> {
+        // closing a group must not swallow the paren that closes the one around it
+        let input = "
+            2 * (3 + (4 - a));
+            ((a + b)) * c;
+        ".to_string();
+
+        let values = vec![
+          "(2 * (3 + (4 - a)))",
+          "((a + b) * c)",
+        ];
+
+        let l = Lexer::new(input);
+        let mut p = Parser::new(l);
+        let program = p.parse_program();
+        check_parsing_errors(p);
+
+        if program.statements.len() != 2 {
+            return Err(format!("Program statements does not contain 2 statements, got: {}", program.statements.len()));
+        }
+        for (statement, value) in program.statements.iter().zip(values) {
+            assert_eq!(value.to_string(), statement.get_expression());
+        }
+        Ok(())
+    }
+    
     #[test]
     fn test_if_expressions() -> Result<(), String> {
         let input = "if (x < y) { x }".to_string();
@@ -893,6 +1288,105 @@ mod test{
         Ok(())
     } 
     #[test]
+    #[cfg(not(feature = "bigint"))]
+    fn test_integer_literal_range() -> Result<(), String> {
+        let input = "9223372036854775807; 9223372036854775808; 99999999999999999999;".to_string();
+
+        let l = Lexer::new(input);
+        let mut p = Parser::new(l);
+        let result = p.parse();
+
+        assert_eq!(result.program.statements.len(), 1);
+        assert_eq!(result.program.statements[0].get_expression(), "9223372036854775807");
+        assert_eq!(result.errors.len(), 2);
+        for (error, found) in result.errors.iter().zip(["9223372036854775808", "99999999999999999999"]) {
+            assert_eq!(error.kind, ParseErrorKind::IntegerOutOfRange);
+            assert_eq!(error.found, Token::INT(found.to_string()));
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_float_literals() -> Result<(), String> {
+        let input = "3.14; 1e-9; .5; -2.5e3; 1e999;".to_string();
+
+        let l = Lexer::new(input);
+        let mut p = Parser::new(l);
+        let result = p.parse();
+
+        assert_eq!(result.program.statements.len(), 4);
+        for (statement, expected) in result.program.statements.iter().zip(["3.14", "1e-9", ".5", "(- 2.5e3)"]) {
+            assert_eq!(statement.get_expression(), expected);
+        }
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].kind, ParseErrorKind::FloatOutOfRange);
+        assert_eq!(result.errors[0].found, Token::FLOAT("1e999".to_string()));
+        Ok(())
+    }
+    #[test]
+    fn test_comments_are_skipped() -> Result<(), String> {
+        let input = "// setup\nlet a = /* five */ 5;\nlet b = a // the same\n  / 1;".to_string();
+
+        for keep_comments in [false, true] {
+            let mut l = Lexer::new(input.clone());
+            l.keep_comments = keep_comments;
+            let mut p = Parser::new(l);
+            let result = p.parse();
+
+            assert!(result.errors.is_empty(), "errors: {:?}", result.errors);
+            let statements: Vec<String> = result.program.statements.iter().map(|statement| statement.to_string()).collect();
+            assert_eq!(statements, vec!["Name: a, Value: 5", "Name: b, Value: (a / 1)"]);
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_interpolated_strings() -> Result<(), String> {
+        let input = vec![
+            (r#""Hello ${name}, you have ${count + 1} items""#, "Hello ${name}, you have ${(count + 1)} items"),
+            (r#""${a}${b}""#, "${a}${b}"),
+            (r#""outer ${"inner ${x}"}""#, "outer ${inner ${x}}"),
+            (r#""${ {"a": 1}["a"] }""#, "${({a: 1}[a])}"),
+        ];
+        for (entry, expected) in input {
+            let l = Lexer::new(entry.to_string());
+            let mut p = Parser::new(l);
+            let result = p.parse();
+            assert!(result.errors.is_empty(), "input: {}, errors: {:?}", entry, result.errors);
+            assert_eq!(result.program.statements.len(), 1);
+            assert_eq!(result.program.statements[0].get_expression(), expected);
+        }
+
+        let input = vec![
+            (r#""${}""#, Token::INTERPEND("".to_string())),
+            (r#""${a b}""#, Token::IDENT("b".to_string())),
+        ];
+        for (entry, found) in input {
+            let l = Lexer::new(entry.to_string());
+            let mut p = Parser::new(l);
+            let result = p.parse();
+            assert!(result.errors.iter().any(|error| error.found == found), "input: {}, errors: {:?}", entry, result.errors);
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_function_parameter_errors() -> Result<(), String> {
+        let input = vec![
+            ("fn(x, x) { x }", ParseErrorKind::DuplicateParameter("x".to_string()), Token::IDENT("x".to_string())),
+            ("fn(x, 1) { x }", ParseErrorKind::FailedIdent, Token::INT("1".to_string())),
+            ("fn(x y) { x }", ParseErrorKind::InvalidToken, Token::IDENT("y".to_string())),
+            ("fn(x,) { x }", ParseErrorKind::FailedIdent, Token::RPAREN),
+            ("fn([a]) { a }", ParseErrorKind::FailedIdent, Token::LBRACKET),
+        ];
+        for (entry, kind, found) in input {
+            let l = Lexer::new(entry.to_string());
+            let mut p = Parser::new(l);
+            let result = p.parse();
+            assert!(!result.errors.is_empty(), "input: {}", entry);
+            assert_eq!(result.errors[0].kind, kind, "input: {}", entry);
+            assert_eq!(result.errors[0].found, found, "input: {}", entry);
+        }
+        Ok(())
+    }
+    #[test]
     fn test_function_call() -> Result<(), String> {
         let input = "
         add(1,2 * 3,4 + 5);
@@ -950,4 +1444,252 @@ mod test{
         Ok(())
     }
 
+    #[test]
+    fn test_statement_spans() -> Result<(), String> {
+        let input = "let a = 1 + 2;\nreturn a;\nfn(x) {\n  x\n}".to_string();
+
+        let l = Lexer::new(input);
+        let mut p = Parser::new(l);
+        let program = p.parse_program();
+        check_parsing_errors(p);
+
+        if program.statements.len() != 3 {
+            return Err(format!("Program statements does not contain 3 statements, got: {}", program.statements.len()));
+        }
+
+        // (start line, start column, end line, end column)
+        let values = vec![
+            (1, 1, 1, 15),
+            (2, 1, 2, 10),
+            (3, 1, 5, 2),
+        ];
+        for i in 0..values.len() {
+            let span = program.statements[i].span();
+            println!("Statement: {}, span: {}", program.statements[i], span);
+            assert_eq!(values[i], (span.start.line, span.start.column, span.end.line, span.end.column));
+        }
+
+        // the infix expression inside the let statement covers "1 + 2"
+        if let Statement::LetStatement(let_) = &program.statements[0] {
+            let span = let_.value.span();
+            assert_eq!((span.start.offset, span.end.offset), (8, 13));
+        } else {
+            return Err(format!("Expected a let statement, got: {}", program.statements[0]));
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_illegal_token_errors() -> Result<(), String> {
+        let input = "let a = 5; @; let b = 6;".to_string();
+
+        let l = Lexer::new(input);
+        let mut p = Parser::new(l);
+        let program = p.parse_program();
+
+        // the bad character is reported instead of crashing the parser
+        let illegal = ParseErrorKind::Lexical("Illegal character: @".to_string());
+        if !p.errors.iter().any(|error| error.kind == illegal && error.span.start.column == 12) {
+            return Err(format!("Expected an illegal character error, got: {:?}", p.errors));
+        }
+        if program.statements.len() != 2 {
+            return Err(format!("Program statements does not contain 2 statements, got: {}", program.statements.len()));
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_prec_conversion_error() -> () {
+        let l = Lexer::new("let x = 5;".to_string());
+        let p = Parser::new(l);
+
+        // the parser starts on let, peeking at x
+        let error = match token_to_prec_map(p.peek_token.clone(), p.peek_span) {
+            Ok(_) => panic!("x is not an operator"),
+            Err(error) => error,
+        };
+        assert_eq!(error.kind, ParseErrorKind::InvalidPrecConversion);
+        assert_eq!(error.found, Token::IDENT("x".to_string()));
+        assert_eq!((error.span.start.line, error.span.start.column), (1, 5));
+    }
+    #[test]
+    fn test_unreachable_code_warning() -> Result<(), String> {
+        let input = "let f = fn() { return 1; let x = 2; x };\nreturn f();\nf();".to_string();
+        let mut p = Parser::new(Lexer::new(input));
+        let result = p.parse();
+
+        // warnings are reported but do not make the program invalid
+        if result.has_errors() {
+            return Err(format!("Expected only warnings, got: {:?}", result.errors));
+        }
+        let found: Vec<(Token, usize, usize)> = result.errors.iter()
+            .map(|error| (error.found.clone(), error.span.start.line, error.span.start.column))
+            .collect();
+        assert_eq!(found, vec![(Token::LET, 1, 26), (Token::IDENT("f".to_string()), 3, 1)]);
+        for error in &result.errors {
+            assert_eq!(error.kind, ParseErrorKind::UnreachableCode);
+            assert_eq!(error.severity, Severity::Warning);
+        }
+        assert_eq!(result.errors[0].to_string(), "1:26: warning: Unreachable code after return: expected the end of the block, found LET");
+        assert_eq!(result.program.statements.len(), 3);
+        Ok(())
+    }
+    #[test]
+    fn test_parse_result_errors() -> Result<(), String> {
+        let input = "let = 5;\nlet x 5;\nlet y = 10;".to_string();
+
+        let l = Lexer::new(input);
+        let mut p = Parser::new(l);
+        let result = p.parse();
+
+        for error in &result.errors {
+            println!("{}", error);
+        }
+        if !result.has_errors() {
+            return Err("Expected the parse result to have errors".to_string());
+        }
+
+        // (kind, expected, found, line, column)
+        let expected = vec![
+            (ParseErrorKind::FailedIdent, "an identifier", Token::ASSIGN, 1, 5),
+            (ParseErrorKind::InvalidToken, "ASSIGN", Token::INT("5".to_string()), 2, 7),
+        ];
+        for (kind, description, found, line, column) in expected {
+            let error = result.errors.iter().find(|error| error.kind == kind)
+                .ok_or_else(|| format!("Missing error of kind: {}", kind))?;
+            assert_eq!(error.expected, description);
+            assert_eq!(error.found, found);
+            assert_eq!(error.severity, Severity::Error);
+            assert_eq!((error.span.start.line, error.span.start.column), (line, column));
+        }
+        assert_eq!(result.errors[0].to_string(), "1:5: error: Failed to read ident: expected an identifier, found ASSIGN");
+
+        // the valid let statement still makes it into the program
+        assert!(result.program.statements.iter().any(|statement| statement.get_statement_name() == "y"));
+        Ok(())
+    }
+    #[test]
+    fn test_error_recovery() -> Result<(), String> {
+        let input = "
+        let a = 5;
+        let b = ;
+        let c = 10 +;
+        fn(x) { let = 1; x };
+        let d = a + c;
+        @
+        let e = if (a < ) { 1 } else { 2 };
+        let f = 1;
+        ".to_string();
+
+        let l = Lexer::new(input);
+        let mut p = Parser::new(l);
+        let result = p.parse();
+
+        for error in &result.errors {
+            println!("{}", error);
+        }
+        // one error per mistake, no cascade
+        let lines: Vec<usize> = result.errors.iter().map(|error| error.span.start.line).collect();
+        assert_eq!(lines, vec![3, 4, 5, 7, 8]);
+
+        let names: Vec<String> = result.program.statements.iter()
+            .map(|statement| statement.get_statement_name()).collect();
+        let values = vec![
+            "a",
+            "FN (x) { x }",
+            "d",
+            "f",
+        ];
+        assert_eq!(names, values);
+        Ok(())
+    }
+    #[test]
+    fn test_array_literal() -> Result<(), String> {
+        let input = "
+        [1, 2 * 2, 3 + 3];
+        [];
+        myArray[1 + 1];
+        f();
+        ".to_string();
+
+        let l = Lexer::new(input);
+        let mut p = Parser::new(l);
+        let program = p.parse_program();
+        check_parsing_errors(p);
+
+        if program.statements.len() != 4 {
+            return Err(format!("Program statements does not contain 4 statements, got: {}", program.statements.len()));
+        }
+
+        let values = vec![
+            "[1, (2 * 2), (3 + 3)]",
+            "[]",
+            "(myArray[(1 + 1)])",
+            "FN (f)",
+        ];
+        for i in 0..values.len() {
+            let statement = program.statements[i as usize].clone();
+            let name = statement.get_expression();
+
+            assert_eq!(values[i].to_string(), name);
+        };
+        Ok(())
+    }
+    #[test]
+    fn test_hash_literal() -> Result<(), String> {
+        let input = r#"
+        {"one": 1, "two": 2, "three": 3};
+        {};
+        {"one": 0 + 1, true: 10 - 8, 3: 15 / 5};
+        let h = {"a": 1}["a"];
+        {"one" 1};
+        "#.to_string();
+
+        let l = Lexer::new(input);
+        let mut p = Parser::new(l);
+        let result = p.parse();
+
+        let values = vec![
+            "{one: 1, two: 2, three: 3}",
+            "{}",
+            "{one: (0 + 1), true: (10 - 8), 3: (15 / 5)}",
+            "({a: 1}[a])",
+        ];
+        assert_eq!(result.program.statements.len(), values.len());
+        for i in 0..values.len() {
+            let statement = result.program.statements[i].clone();
+            let name = statement.get_expression();
+
+            assert_eq!(values[i].to_string(), name);
+        };
+
+        // a missing colon is reported
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].expected, "COLON");
+        Ok(())
+    }
+
+    #[test]
+    fn test_pretty_print() -> Result<(), String> {
+        let input = "let a = -x + f(1);\nreturn [a][0];".to_string();
+
+        let l = Lexer::new(input);
+        let mut p = Parser::new(l);
+        let program = p.parse_program();
+        check_parsing_errors(p);
+
+        let expected = "Let a @ 1:1
+  Infix + @ 1:9
+    Prefix - @ 1:9
+      Ident x @ 1:10
+    Call @ 1:14
+      Ident f @ 1:14
+      Int 1 @ 1:16
+Return @ 2:1
+  Index @ 2:8
+    Array @ 2:8
+      Ident a @ 2:9
+    Int 0 @ 2:12
+";
+        assert_eq!(expected, program.pretty());
+        Ok(())
+    }
 }
@@ -1,33 +1,299 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Editor, Helper};
 
 use crate::token::object::ObjectType;
 
-use super::token::Lexer;
-use super::parser::Parser;
+use super::token::{Lexer, Token, KEYWORDS, UNTERMINATED_STRING, UNTERMINATED_COMMENT};
+use super::parser::{Parser, Severity};
 use super::evaluator::Evalulator;
 use super::ast;
+use super::script;
+use super::builtins;
 
-pub fn start() -> Result<()> {
-    println!("Input the commands to be translated: ");
+const PROMPT: &str = ">> ";
+const HISTORY_FILE: &str = ".interpreter_history";
+const COMMANDS: [&str; 8] = [":tokens", ":ast", ":env", ":reset", ":load", ":quit", ":q", ":help"];
+const HELP: &str = ":tokens <src>   show the tokens the lexer makes from src
+:ast <src>      show the parsed program for src
+:env            list the current bindings
+:reset          forget every binding
+:load <file>    evaluate a file into the session
+:quit           leave the REPL
+:help           show this message";
 
-    let mut evalulator = Evalulator::new();
-    std::io::stdin().lines().for_each(|input| {
-        if let Ok(input) = input {
-            let l = Lexer::new(input);
-            let mut p = Parser::new(l);
-            let program = p.parse_program();
-            p.check_parsing_errors();
+pub fn start(mut evalulator: Evalulator) -> Result<()> {
+    println!("Input the commands to be translated: ");
 
-            let statement = program.statements[0].clone();
-            let name = statement.get_statement_name();
-            let express = statement.get_expression();
-            println!("Name: {}", name);
-            println!("Expression: {}", express);
-            let evalulated = evalulator.eval(ast::Node::Program(program)).unwrap();
+    let mut editor: Editor<ReplHelper, FileHistory> = Editor::new()?;
+    editor.set_helper(Some(ReplHelper::default()));
+    let history = history_path();
+    if let Some(path) = &history {
+        // there is no history the first time around
+        let _ = editor.load_history(path);
+    }
 
-            println!("{}", evalulated.inspect());
+    loop {
+        // completion offers whatever is bound right now
+        if let Some(helper) = editor.helper_mut() {
+            helper.names = evalulator.environment.borrow().bindings()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect();
+        }
+        let input = match editor.readline(PROMPT) {
+            Ok(input) => input,
+            // ctrl-c drops the current input, ctrl-d leaves
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(error) => return Err(error.into()),
+        };
+        if input.trim().is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(input.as_str());
+        // meta commands are only read at the start of an input
+        if input.trim_start().starts_with(':') {
+            if !run_command(&mut evalulator, input.trim()) {
+                break;
+            }
+            continue;
         }
-    });
+        eval_input(&mut evalulator, input);
+    }
 
+    if let Some(path) = &history {
+        if let Err(error) = editor.save_history(path) {
+            println!("Could not save history to {}: {}", path.display(), error);
+        }
+    }
     Ok(())
 }
+
+fn history_path() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    return Some(PathBuf::from(home).join(HISTORY_FILE));
+}
+
+// hooks rustyline calls back into while a line is being edited
+#[derive(Default)]
+struct ReplHelper {
+    // names bound in the environment when the prompt was shown
+    names: Vec<String>,
+}
+impl ReplHelper {
+    fn candidates(&self, line: &str, prefix: &str) -> Vec<String> {
+        let mut candidates: Vec<String> = Vec::new();
+        if line.trim_start().starts_with(':') && !line.contains(char::is_whitespace) {
+            candidates.extend(COMMANDS.iter().map(|command| command.to_string()));
+        } else {
+            candidates.extend(KEYWORDS.iter().map(|(keyword, _)| keyword.to_string()));
+            candidates.extend(builtins::builtin_names());
+            candidates.extend(self.names.iter().cloned());
+        }
+        candidates.retain(|candidate| candidate.starts_with(prefix));
+        candidates.sort();
+        candidates.dedup();
+        return candidates;
+    }
+}
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = word_start(line, pos);
+        return Ok((start, self.candidates(&line[..pos], &line[start..pos])));
+    }
+}
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+impl Highlighter for ReplHelper {}
+impl Validator for ReplHelper {
+    // keep reading lines while brackets are open
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = ctx.input();
+        if input.trim_start().starts_with(':') || is_complete(input) {
+            return Ok(ValidationResult::Valid(None));
+        }
+        return Ok(ValidationResult::Incomplete);
+    }
+}
+impl Helper for ReplHelper {}
+
+// the word being completed, a leading : is kept for meta commands
+fn word_start(line: &str, pos: usize) -> usize {
+    let mut start = pos;
+    for (index, ch) in line[..pos].char_indices().rev() {
+        if ch.is_alphanumeric() || ch == '_' {
+            start = index;
+        } else {
+            if ch == ':' {
+                start = index;
+            }
+            break;
+        }
+    }
+    return start;
+}
+
+fn eval_input(evalulator: &mut Evalulator, input: String) {
+    // errors are printed and the session keeps going
+    let l = Lexer::new(input);
+    let mut p = Parser::new(l);
+    let program = p.parse_program();
+    if p.errors().iter().any(|error| error.severity == Severity::Error) {
+        p.check_parsing_errors();
+        return;
+    }
+    // warnings are shown and the input still runs
+    for warning in p.errors() {
+        println!("{}", warning);
+    }
+
+    match evalulator.eval(ast::Node::Program(program)) {
+        Ok(evalulated) => println!("{}", evalulated.inspect()),
+        Err(error) => println!("{}", error),
+    }
+}
+
+// returns false when the REPL should stop
+fn run_command(evalulator: &mut Evalulator, line: &str) -> bool {
+    let (command, argument) = match line.split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (line, ""),
+    };
+    match command {
+        ":tokens" => print_tokens(argument),
+        ":ast" => print_ast(argument),
+        ":env" => {
+            for (name, value) in evalulator.environment.borrow().bindings() {
+                println!("{} = {}", name, value.inspect());
+            }
+        },
+        ":reset" => {
+            evalulator.reset();
+            println!("Environment cleared");
+        },
+        ":load" => match script::run_file(evalulator, argument) {
+            Ok(Ok(evalulated)) => println!("{}", evalulated.inspect()),
+            Ok(Err(error)) => println!("{}", error),
+            Err(error) => println!("Could not read {}: {}", argument, error),
+        },
+        ":quit" | ":q" => return false,
+        ":help" => println!("{}", HELP),
+        _ => println!("Unknown command: {}, try :help", command),
+    }
+    return true;
+}
+
+fn print_tokens(input: &str) {
+    let mut lex = Lexer::new(input.to_string());
+    lex.keep_comments = true;
+    loop {
+        match lex.next_spanned_token() {
+            Ok(spanned) => {
+                println!("{} {}", spanned.span.start, spanned.token);
+                if spanned.token == Token::EOF {
+                    break;
+                }
+            },
+            Err(error) => {
+                println!("{}", error);
+                break;
+            },
+        }
+    }
+}
+
+fn print_ast(input: &str) {
+    let l = Lexer::new(input.to_string());
+    let mut p = Parser::new(l);
+    let result = p.parse();
+    for statement in &result.program.statements {
+        println!("Name: {}", statement.get_statement_name());
+        println!("Expression: {}", statement.get_expression());
+    }
+    print!("{}", result.program.pretty());
+    for error in &result.errors {
+        println!("{}", error);
+    }
+}
+
+// the input is complete once every (, { and [ and every string and comment has been closed
+pub fn is_complete(input: &str) -> bool {
+    let mut lex = Lexer::new(input.to_string());
+    let mut depth: i64 = 0;
+    loop {
+        match lex.next_token() {
+            Ok(Token::LPAREN) | Ok(Token::LBRACE) | Ok(Token::LBRACKET) => depth += 1,
+            Ok(Token::RPAREN) | Ok(Token::RBRACE) | Ok(Token::RBRACKET) => depth -= 1,
+            Ok(Token::EOF) | Err(_) => break,
+            _ => {},
+        }
+    }
+    // a string or comment left open carries on over the next line
+    let unterminated = [UNTERMINATED_STRING, UNTERMINATED_COMMENT];
+    if lex.diagnostics.iter().any(|diagnostic| unterminated.contains(&diagnostic.message.as_str())) {
+        return false;
+    }
+    // too many closing brackets is an error for the parser to report, not more input
+    return depth <= 0;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{is_complete, word_start, ReplHelper};
+
+    #[test]
+    fn test_is_complete() -> () {
+        let input = vec![
+            ("let a = 5;", true),
+            ("", true),
+            ("let add = fn(x, y) {", false),
+            ("let add = fn(x, y) {\n x + y;\n};", true),
+            ("add(1,", false),
+            ("[1, 2,\n 3]", true),
+            (r#"let s = "{(";"#, true),
+            ("}", true),
+            ("let t = `line one", false),
+            ("let t = `line one\nline two`;", true),
+            (r#"let s = "ends in \"#, false),
+            ("let a = 1; /* still", false),
+            ("let a = 1; /* done */", true),
+            ("let a = 1; // {", true),
+            (r#"let s = "${a"#, false),
+            (r#"let s = "${ {"#, false),
+            (r#"let s = "${a} {";"#, true),
+        ];
+        for (entry, expected) in input {
+            assert_eq!(is_complete(entry), expected, "input: {}", entry);
+        }
+    }
+
+    #[test]
+    fn test_completion_candidates() -> () {
+        let helper = ReplHelper {
+            names: vec!["counter".to_string(), "result".to_string()],
+        };
+        let input = vec![
+            ("le", vec!["len", "let"]),
+            ("let x = co", vec!["counter"]),
+            ("re", vec!["rest", "result", "return"]),
+            (":e", vec![":env"]),
+            (":ast fi", vec!["first"]),
+        ];
+        for (line, expected) in input {
+            let start = word_start(line, line.len());
+            let candidates = helper.candidates(line, &line[start..]);
+            assert_eq!(candidates, expected, "line: {}", line);
+        }
+    }
+}
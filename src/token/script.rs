@@ -0,0 +1,78 @@
+use std::fmt::Display;
+
+use super::ast::Node;
+use super::evaluator::{Evalulator, EvalError};
+use super::object::Object;
+use super::parser::{Parser, ParseError};
+use super::token::Lexer;
+
+#[derive(Debug)]
+pub enum RunError {
+    // every error the parser found, nothing was evaluated
+    Parse(Vec<ParseError>),
+    Eval(EvalError),
+}
+impl Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::Parse(errors) => {
+                let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+                write!(f, "{}", messages.join("\n"))
+            },
+            RunError::Eval(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+// parse the whole source as one program and evaluate it in the evalulator's environment
+pub fn run(evalulator: &mut Evalulator, source: String) -> Result<Object, RunError> {
+    let l = Lexer::new(source);
+    let mut p = Parser::new(l);
+    let result = p.parse();
+    if result.has_errors() {
+        return Err(RunError::Parse(result.errors));
+    }
+    // warnings do not stop the run, so they go to stderr
+    for warning in &result.errors {
+        eprintln!("{}", warning);
+    }
+    return evalulator.eval(Node::Program(result.program)).map_err(RunError::Eval);
+}
+
+pub fn run_file(evalulator: &mut Evalulator, path: &str) -> anyhow::Result<Result<Object, RunError>> {
+    let source = std::fs::read_to_string(path)?;
+    return Ok(run(evalulator, source));
+}
+
+#[cfg(test)]
+mod test {
+    use super::{run, RunError};
+    use crate::token::evaluator::{Evalulator, EvalError};
+    use crate::token::object::ObjectType;
+
+    #[test]
+    fn test_run() -> Result<(), RunError> {
+        let mut evalulator = Evalulator::new();
+        let input = "
+        let add = fn(x, y) { x + y; };
+        let a = add(1, 2);
+        a * 2
+        ".to_string();
+        assert_eq!(run(&mut evalulator, input)?.inspect(), "6");
+        // the bindings stay around for the next run
+        assert_eq!(run(&mut evalulator, "a".to_string())?.inspect(), "3");
+        Ok(())
+    }
+    #[test]
+    fn test_run_errors() -> () {
+        let mut evalulator = Evalulator::new();
+        match run(&mut evalulator, "let = 1; let b = ;".to_string()) {
+            Err(RunError::Parse(errors)) => assert_eq!(errors.len(), 2),
+            other => panic!("expected parse errors, got: {:?}", other),
+        }
+        match run(&mut evalulator, "1 + true".to_string()) {
+            Err(RunError::Eval(EvalError::Mismatched(..))) => {},
+            other => panic!("expected an eval error, got: {:?}", other),
+        }
+    }
+}
@@ -3,13 +3,21 @@ use anyhow::Result;
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     //Specials
-    ILLEGAL,
+    ILLEGAL(String),
     EOF,
+    // only made when the lexer keeps comments, the whole comment with its slashes
+    COMMENT(String),
 
     // Identifiers + Literals
     IDENT(String),
     INT(String),
+    FLOAT(String),
     STRING(String),
+    // "Hello ${name}, you have ${count} items" is
+    // INTERPSTART(Hello ) name INTERPMID(, you have ) count INTERPEND( items)
+    INTERPSTART(String),
+    INTERPMID(String),
+    INTERPEND(String),
 
     // Operators
     ASSIGN,
@@ -24,15 +32,20 @@ pub enum Token {
     GEQUAL,
     EQUAL,
     NEQUAL,
+    AND,
+    OR,
 
     // Delimeters
     COMMA,
     SEMICOLON,
+    COLON,
 
     LPAREN,
     RPAREN,
     LBRACE,
     RBRACE,
+    LBRACKET,
+    RBRACKET,
 
     // Keywords
     FUNCTION,
@@ -47,11 +60,16 @@ pub enum Token {
 impl std::fmt::Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         return match self {
-            Token::ILLEGAL => write!(f, "ILLEGAL"),
+            Token::ILLEGAL(string) => write!(f, "ILLEGAL({})", string),
             Token::EOF => write!(f, "EOF"),
+            Token::COMMENT(string) => write!(f, "COMMENT({})", string),
             Token::IDENT(string) => write!(f, "IDENT({})", string),
             Token::INT(string) => write!(f, "{}", string),
+            Token::FLOAT(string) => write!(f, "{}", string),
             Token::STRING(string) => write!(f, "{}", string),
+            Token::INTERPSTART(string) => write!(f, "INTERPSTART({})", string),
+            Token::INTERPMID(string) => write!(f, "INTERPMID({})", string),
+            Token::INTERPEND(string) => write!(f, "INTERPEND({})", string),
             Token::ASSIGN => write!(f, "ASSIGN"),
             Token::BANG => write!(f, "BANG"),
             Token::PLUS => write!(f, "PLUS"),
@@ -64,12 +82,17 @@ impl std::fmt::Display for Token {
             Token::GEQUAL => write!(f, "GEQUAL"),
             Token::EQUAL => write!(f, "EQUAL"),
             Token::NEQUAL => write!(f, "NEQUAL"),
+            Token::AND => write!(f, "AND"),
+            Token::OR => write!(f, "OR"),
             Token::COMMA => write!(f, "COMMA"),
             Token::SEMICOLON => write!(f, "SEMICOLON"),
+            Token::COLON => write!(f, "COLON"),
             Token::LPAREN => write!(f, "LPAREN"),
             Token::RPAREN => write!(f, "RPAREN"),
             Token::LBRACE => write!(f, "LBRACE"),
             Token::RBRACE => write!(f, "RBRACE"),
+            Token::LBRACKET => write!(f, "LBRACKET"),
+            Token::RBRACKET => write!(f, "RBRACKET"),
             Token::FUNCTION => write!(f, "FUNCTION"),
             Token::LET => write!(f, "LET"),
             Token::IF => write!(f, "IF"),
@@ -80,6 +103,26 @@ impl std::fmt::Display for Token {
         }
     }
 }
+// every word the lexer turns into something other than an IDENT
+pub const KEYWORDS: [(&str, Token); 7] = [
+    ("fn", Token::FUNCTION),
+    ("let", Token::LET),
+    ("if", Token::IF),
+    ("else", Token::ELSE),
+    ("true", Token::TRUE),
+    ("false", Token::FALSE),
+    ("return", Token::RETURN),
+];
+
+pub fn lookup_ident(ident: String) -> Token {
+    for (keyword, token) in KEYWORDS {
+        if keyword == ident {
+            return token;
+        }
+    }
+    return Token::IDENT(ident);
+}
+
 #[allow(unused)]
 impl Token {
     fn to_string(&self) -> String {
@@ -88,37 +131,125 @@ impl Token {
     }
 }
 
+// line and column start at 1 and count chars, offset is the byte offset into the input
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+// start is the first char of the source, end is one passed the last char
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+impl Span {
+    pub fn new(start: Position, end: Position) -> Span {
+        Span { start, end }
+    }
+    // join two spans: start of self through the end of other
+    pub fn to(&self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end,
+        }
+    }
+}
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+// problems found while lexing, the lexer keeps going and the parser reports them
+#[derive(Debug, PartialEq, Clone)]
+pub struct LexError {
+    pub message: String,
+    pub span: Span,
+}
+impl std::fmt::Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}", self.message, self.span.start)
+    }
+}
+
+// message for a string still open at the end of the input, the REPL waits for more lines on it
+pub const UNTERMINATED_STRING: &str = "Unterminated string";
+pub const UNTERMINATED_COMMENT: &str = "Unterminated block comment";
+
 pub struct Lexer {
-   pub input: Vec<u8>,
+   pub input: Vec<char>,
    pub position: usize,
    pub read_position: usize,
-   pub ch: u8,
+   pub ch: char,
+   // line and column of ch, columns count chars
+   pub line: usize,
+   pub column: usize,
+   // byte offset of ch in the source
+   pub offset: usize,
+   pub diagnostics: Vec<LexError>,
+   // hand comments out as COMMENT tokens instead of skipping them, for tools that rewrite source
+   pub keep_comments: bool,
+   // one entry per ${ still open: where its string started and how many { are open inside it
+   interpolation: Vec<(Position, usize)>,
 }
 impl Default for Lexer {
     fn default() -> Self {
         Self {
-        input: Vec::<u8>::new(),
+        input: Vec::<char>::new(),
         position: 0,
         read_position: 0,
-        ch: 0
+        ch: EOF,
+        line: 1,
+        column: 0,
+        offset: 0,
+        diagnostics: Vec::<LexError>::new(),
+        keep_comments: false,
+        interpolation: Vec::new(),
         }
     }
 }
+// the char ch holds once the input runs out
+const EOF: char = '\0';
+
 impl Lexer {
     // Set default variables for lexer
     pub fn new(input: String) -> Lexer {
         let mut lex = Lexer::default();
-        lex.input = input.into_bytes();
+        lex.input = input.chars().collect();
         lex.read_char();
         return lex;
     }
 
     // Take a look at the input then return the next char
-    fn read_char(&mut self) -> u8 {
-        // if we reached the EOF char returns 0 (EOF)
+    fn read_char(&mut self) -> char {
+        // moving passed a newline starts the next line
+        if self.ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        if self.read_position > 0 {
+            self.offset += self.ch.len_utf8();
+        }
+        // if we reached the EOF char returns EOF
         //  else set ch to the next positon
         if self.read_position >= self.input.len() {
-            self.ch = 0;
+            self.ch = EOF;
         } else {
             self.ch = self.input[self.read_position];
         }
@@ -128,170 +259,414 @@ impl Lexer {
     }
 
     pub fn next_token(&mut self)-> Result<Token> {
+        return Ok(self.next_spanned_token()?.token);
+    }
+
+    pub fn next_spanned_token(&mut self) -> Result<SpannedToken> {
         // " let x = 4;"
         // skip whitespace = "let x = 4;"
+        // mark the start, read the token, mark the end
+        self.skip_whitespace();
+        while !self.keep_comments && self.at_comment() {
+            self.read_comment();
+            self.skip_whitespace();
+        }
+        let start = self.current_position();
+        let token = match self.at_comment() {
+            true => Token::COMMENT(self.read_comment()),
+            false => self.read_token()?,
+        };
+        let end = self.current_position();
+        let span = Span::new(start, end);
+        if let Token::ILLEGAL(text) = &token {
+            self.diagnostics.push(LexError {
+                message: format!("Illegal character: {}", text),
+                span,
+            });
+        }
+        return Ok(SpannedToken { token, span });
+    }
+
+    pub fn current_position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+            offset: self.offset,
+        }
+    }
+
+    fn read_token(&mut self)-> Result<Token> {
         // match for let = "{Token::LET} x = 4;"
         // read_char to move up = Token::LET + " x = 4;"
         // return Token::LET;
-        self.skip_whitespace();
         let tok = match self.ch {
-            b'=' => {
+            '=' => {
                 let next_char = self.peek_char();
-                if next_char == b'=' {
+                if next_char == '=' {
                     self.read_char();
                     Token::EQUAL
                 } else {
                     Token::ASSIGN
                 }
             },      // =, ==
-            b';' => Token::SEMICOLON,
-            b'(' => Token::LPAREN,
-            b')' => Token::RPAREN,
-            b'{' => Token::LBRACE,
-            b'}' => Token::RBRACE,
-            b',' => Token::COMMA,
-            b'+' => Token::PLUS,
-            b'-' => Token::SUBTRACT,
-            b'/' => Token::FSLASH,
-            b'*' => Token::STAR,
-            b'!' => {
+            ';' => Token::SEMICOLON,
+            ':' => Token::COLON,
+            '(' => Token::LPAREN,
+            ')' => Token::RPAREN,
+            '{' => {
+                if let Some((_, depth)) = self.interpolation.last_mut() {
+                    *depth += 1;
+                }
+                Token::LBRACE
+            },
+            // the } that closes a ${ goes back to reading the string
+            '}' if matches!(self.interpolation.last(), Some((_, 0))) => {
+                return Ok(self.read_string_continue())
+            },
+            '}' => {
+                if let Some((_, depth)) = self.interpolation.last_mut() {
+                    *depth -= 1;
+                }
+                Token::RBRACE
+            },
+            '[' => Token::LBRACKET,
+            ']' => Token::RBRACKET,
+            ',' => Token::COMMA,
+            '+' => Token::PLUS,
+            '-' => Token::SUBTRACT,
+            '/' => Token::FSLASH,
+            '*' => Token::STAR,
+            '!' => {
                 let next_char = self.peek_char();
-                if next_char == b'=' {
+                if next_char == '=' {
                     self.read_char();
                     Token::NEQUAL
                 } else {
                     Token::BANG
                 }
             },        // !=, !
-            b'<' => {
+            '<' => {
                 let next_char = self.peek_char();
-                if next_char == b'=' {
+                if next_char == '=' {
                     self.read_char();
                     Token::LEQUAL
                 } else {
                     Token::LTHAN
                 }
             },       // >=, <=
-            b'>' => {
+            '>' => {
                 let next_char = self.peek_char();
-                if next_char == b'=' {
+                if next_char == '=' {
                     self.read_char();
                     Token::GEQUAL
                 } else {
                     Token::GTHAN
                 }
             },
-            b'"' => {
-                let string = self.read_string();
+            '&' => {
+                let next_char = self.peek_char();
+                if next_char == '&' {
+                    self.read_char();
+                    Token::AND
+                } else {
+                    Token::ILLEGAL("&".to_string())
+                }
+            },       // &&
+            '|' => {
+                let next_char = self.peek_char();
+                if next_char == '|' {
+                    self.read_char();
+                    Token::OR
+                } else {
+                    Token::ILLEGAL("|".to_string())
+                }
+            },       // ||
+            '"' => {
+                return Ok(self.read_string())
+            }
+            '`' => {
+                let string = self.read_raw_string();
                 return Ok( Token::STRING(string) )
             }
-            b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+            ch if Lexer::is_ident_start(ch) => {
                 let ident = self.read_ident();
-                return Ok(match ident.as_str() {
-                    "fn" => Token::FUNCTION,
-                    "let" =>  Token::LET,
-                    "if" => Token::IF,
-                    "else" => Token::ELSE,
-                    "true" => Token::TRUE,
-                    "false" => Token::FALSE,
-                    "return" => Token::RETURN,
-                    _ => Token::IDENT(ident),
-                });
+                return Ok(lookup_ident(ident));
             },
-            b'0'..=b'9' => {
-                return Ok(Token::INT(self.read_number()))
+            // .5 is a number, a lone . is still illegal
+            '0'..='9' | '.' if Lexer::is_number(self.ch) || Lexer::is_number(self.peek_char()) => {
+                return Ok(self.read_number())
             },
-            0 => Token::EOF,
-            i => {
-                if i.is_ascii() {
-                    let shitty = vec![i];
-                    let output = std::str::from_utf8(&shitty)?;
-                    unreachable!("Unreachable option for TOKEN: {output}")
-                } else {
-                    unreachable!("Unreachable option for TOKEN {i}")
+            EOF => {
+                // the input ended inside a ${
+                while let Some((start, _)) = self.interpolation.pop() {
+                    self.unterminated(start);
                 }
+                Token::EOF
             },
+            ch => Token::ILLEGAL(ch.to_string()),
         };
         self.read_char();
         return Ok(tok)
     }
 
-    fn peek_char(&mut self) -> u8 {
-        if self.position > self.read_position {
-            return 0
-        } else {
-            return self.input[self.read_position]
-        }
+    fn peek_char(&self) -> char {
+        return self.peek_nth(0)
+    }
+    // n chars passed the one after ch, reading passed the end of the input is EOF, not a panic
+    fn peek_nth(&self, n: usize) -> char {
+        return self.input.get(self.read_position + n).copied().unwrap_or(EOF)
     }
 
-    fn is_letter(ch: u8) -> bool {
-        if ch >= b'a' && ch <= b'z' || ch >= b'A' && ch <= b'Z' || ch == b'_' {
-            return true
-        } else {
-            return false 
-        }
+    // identifiers follow Unicode's XID rules, plus a leading _
+    fn is_ident_start(ch: char) -> bool {
+        return ch == '_' || unicode_ident::is_xid_start(ch)
+    }
+    fn is_ident_continue(ch: char) -> bool {
+        return unicode_ident::is_xid_continue(ch)
     }
 
     fn read_ident(&mut self) -> String {
        let position = self.position;
-       while Lexer::is_letter(self.ch) {
+       while Lexer::is_ident_continue(self.ch) {
            self.read_char();
        }
-       let output = std::str::from_utf8(&self.input[position..self.position]).unwrap().to_string();
-       return output
+       return self.text(position)
+    }
+    // the source from position up to ch
+    fn text(&self, position: usize) -> String {
+        let end = self.position.min(self.input.len());
+        return self.input[position..end].iter().collect()
     }
-    fn read_string(&mut self) -> String {
-        // input = "hello world"
+    fn read_string(&mut self) -> Token {
+        // input = "hello\tworld"
+        let start = self.current_position();
         //  skip the '"'
         self.read_char();
-        // input = hello world"
-        let position = self.position;
-        while Lexer::is_still_string(self.ch) {
+        let (string, open) = self.read_string_part(start);
+        if open {
+            self.interpolation.push((start, 0));
+            return Token::INTERPSTART(string)
+        }
+        return Token::STRING(string)
+    }
+    // ch is the } closing a ${, the rest of the string follows it
+    fn read_string_continue(&mut self) -> Token {
+        let (start, _) = self.interpolation.pop().unwrap();
+        self.read_char();
+        let (string, open) = self.read_string_part(start);
+        if open {
+            self.interpolation.push((start, 0));
+            return Token::INTERPMID(string)
+        }
+        return Token::INTERPEND(string)
+    }
+    // text up to the closing quote, or up to a ${ which is left open (true)
+    fn read_string_part(&mut self, start: Position) -> (String, bool) {
+        let mut output = String::new();
+        loop {
+            match self.ch {
+                '"' => break,
+                EOF => {
+                    self.unterminated(start);
+                    return (output, false)
+                },
+                '$' if self.peek_char() == '{' => {
+                    // skip the ${, the tokens of the expression come next
+                    self.read_char();
+                    self.read_char();
+                    return (output, true)
+                },
+                '\\' => {
+                    let escape = self.current_position();
+                    self.read_char();
+                    // a lone \ at the end is only an unterminated string
+                    if self.ch == EOF {
+                        continue;
+                    }
+                    if let Some(ch) = self.read_escape(escape) {
+                        output.push(ch);
+                    }
+                },
+                ch => output.push(ch),
+            }
             self.read_char();
         }
-        let output = std::str::from_utf8(&self.input[position..self.position]).unwrap().to_string();
-        self.skip_whitespace();
+        // move passed the closing quote, whatever follows is left for the next token
         self.read_char();
-        return output
+        return (output, false)
     }
-    fn is_number(ch: u8) -> bool {
-        if ch >= b'0' && ch <= b'9' {
-            return true 
-        } else {
-            return false
+    // ch is the char after the \, it is left on the last char of the escape
+    // a bad escape is reported and dropped from the string
+    fn read_escape(&mut self, start: Position) -> Option<char> {
+        match self.ch {
+            'n' => return Some('\n'),
+            't' => return Some('\t'),
+            'r' => return Some('\r'),
+            '0' => return Some('\0'),
+            '"' => return Some('"'),
+            '\\' => return Some('\\'),
+            '$' => return Some('$'),
+            'u' => {},
+            ch => {
+                self.escape_error(start, format!("Unknown escape sequence: \\{}", ch));
+                return None
+            },
+        }
+        // \u{1F600}, one to six hex digits naming a char
+        // only peek at what comes next so a closing quote is never swallowed
+        if self.peek_char() != '{' {
+            self.escape_error(start, "Invalid unicode escape: expected {".to_string());
+            return None
+        }
+        self.read_char();
+        let mut digits = String::new();
+        while self.peek_char().is_ascii_hexdigit() {
+            self.read_char();
+            digits.push(self.ch);
+        }
+        if self.peek_char() != '}' {
+            self.escape_error(start, "Invalid unicode escape: expected }".to_string());
+            return None
+        }
+        self.read_char();
+        let ch = u32::from_str_radix(&digits, 16).ok()
+            .filter(|_| digits.len() <= 6)
+            .and_then(char::from_u32);
+        if ch.is_none() {
+            self.escape_error(start, format!("Invalid unicode escape: \\u{{{}}}", digits));
         }
+        return ch
     }
-    fn is_still_string(ch: u8) -> bool {
-        if ch == b'"' || ch == 0 {
-            return false
+    // `raw string`, no escapes and newlines are kept as they are
+    fn read_raw_string(&mut self) -> String {
+        let start = self.current_position();
+        self.read_char();
+        let position = self.position;
+        while self.ch != '`' && self.ch != EOF {
+            self.read_char();
+        }
+        let output = self.text(position);
+        if self.ch == EOF {
+            self.unterminated(start);
         } else {
-            return true 
+            self.read_char();
         }
+        return output
+    }
+    fn unterminated(&mut self, start: Position) {
+        self.diagnostics.push(LexError {
+            message: UNTERMINATED_STRING.to_string(),
+            span: Span::new(start, self.current_position()),
+        });
     }
-    fn read_number(&mut self) -> String {
+    // from the \ through the char the lexer is sitting on
+    fn escape_error(&mut self, start: Position, message: String) {
+        let mut end = self.current_position();
+        end.column += 1;
+        end.offset += self.ch.len_utf8();
+        self.diagnostics.push(LexError {
+            message,
+            span: Span::new(start, end),
+        });
+    }
+    fn is_number(ch: char) -> bool {
+        return ch.is_ascii_digit()
+    }
+    fn read_number(&mut self) -> Token {
         let position = self.position;
+        let mut float = false;
         while Lexer::is_number(self.ch) {
             self.read_char();
         }
-        let output = std::str::from_utf8(&self.input[position..self.position]).unwrap().to_string();
-        return output
+        // a fraction needs a digit after the dot
+        if self.ch == '.' && Lexer::is_number(self.peek_char()) {
+            float = true;
+            self.read_char();
+            while Lexer::is_number(self.ch) {
+                self.read_char();
+            }
+        }
+        // so does an exponent, otherwise the e is left to start an identifier
+        if (self.ch == 'e' || self.ch == 'E') && self.exponent_follows() {
+            float = true;
+            self.read_char();
+            if self.ch == '+' || self.ch == '-' {
+                self.read_char();
+            }
+            while Lexer::is_number(self.ch) {
+                self.read_char();
+            }
+        }
+        let output = self.text(position);
+        if float {
+            return Token::FLOAT(output)
+        }
+        return Token::INT(output)
+    }
+    fn exponent_follows(&self) -> bool {
+        match self.peek_char() {
+            '+' | '-' => return Lexer::is_number(self.peek_nth(1)),
+            ch => return Lexer::is_number(ch),
+        }
     }
     
+    // a lone / is still FSLASH
+    fn at_comment(&self) -> bool {
+        return self.ch == '/' && (self.peek_char() == '/' || self.peek_char() == '*')
+    }
+    // // runs to the end of the line, /* */ can hold other /* */ inside it
+    fn read_comment(&mut self) -> String {
+        let start = self.current_position();
+        let position = self.position;
+        if self.peek_char() == '/' {
+            while self.ch != '\n' && self.ch != EOF {
+                self.read_char();
+            }
+        } else {
+            // skip the /*
+            self.read_char();
+            self.read_char();
+            let mut depth = 1;
+            while depth > 0 {
+                match (self.ch, self.peek_char()) {
+                    (EOF, _) => {
+                        self.diagnostics.push(LexError {
+                            message: UNTERMINATED_COMMENT.to_string(),
+                            span: Span::new(start, self.current_position()),
+                        });
+                        break;
+                    },
+                    ('/', '*') => {
+                        depth += 1;
+                        self.read_char();
+                    },
+                    ('*', '/') => {
+                        depth -= 1;
+                        self.read_char();
+                    },
+                    _ => {},
+                }
+                self.read_char();
+            }
+        }
+        return self.text(position)
+    }
     fn skip_whitespace(&mut self) {
-        while self.ch == b' ' || self.ch == b'\t' || self.ch == b'\n' || self.ch == b'\r' {
+        while self.ch.is_whitespace() {
             self.read_char();
         }
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod test{
     use anyhow::Result;
-    use super::{Lexer, Token};
+    use super::{Lexer, Token, Position};
 
     #[test]
     pub fn test_next_token() -> Result<()> {
-        let input = "=+(){},;".to_string();
+        let input = "=+(){},;[]:".to_string();
         let options = vec![
         Token::ASSIGN,
         Token::PLUS,
@@ -301,6 +676,9 @@ mod test{
         Token::RBRACE,
         Token::COMMA,
         Token::SEMICOLON,
+        Token::LBRACKET,
+        Token::RBRACKET,
+        Token::COLON,
         Token::EOF,
         ];
 
@@ -380,7 +758,7 @@ mod test{
             x + y;
         };
         let result = add(five, ten);
-        !-/*5;
+        !-/ *5;
         5 < 10 > 5; 
         ".to_string();
         let options = vec![
@@ -452,7 +830,7 @@ mod test{
             x + y;
         };
         let result = add(five, ten);
-        !-/*5;
+        !-/ *5;
         5 < 10 > 5;
         if (5 < 10) {
           return true;
@@ -566,4 +944,313 @@ mod test{
         }
         Ok(())
     }
+    #[test]
+    pub fn test_token_spans() -> Result<()> {
+        let input = "let x = 5;\n  x == 10;".to_string();
+
+        // (token, start line, start column, start offset, end column)
+        let options = vec![
+            (Token::LET, 1, 1, 0, 4),
+            (Token::IDENT("x".to_string()), 1, 5, 4, 6),
+            (Token::ASSIGN, 1, 7, 6, 8),
+            (Token::INT("5".to_string()), 1, 9, 8, 10),
+            (Token::SEMICOLON, 1, 10, 9, 11),
+            (Token::IDENT("x".to_string()), 2, 3, 13, 4),
+            (Token::EQUAL, 2, 5, 15, 7),
+            (Token::INT("10".to_string()), 2, 8, 18, 10),
+            (Token::SEMICOLON, 2, 10, 20, 11),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for (token, line, column, offset, end_column) in options {
+            let spanned = lex.next_spanned_token()?;
+            println!("Expected: {token}, got: {} at {}", spanned.token, spanned.span);
+            assert_eq!(spanned.token, token);
+            assert_eq!(spanned.span.start, Position { line, column, offset });
+            assert_eq!(spanned.span.end.line, line);
+            assert_eq!(spanned.span.end.column, end_column);
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_illegal_tokens() -> Result<()> {
+        let input = "let a = 5 @ 3; %\n€".to_string();
+
+        let options = vec![
+            Token::LET,
+            Token::IDENT("a".to_string()),
+            Token::ASSIGN,
+            Token::INT("5".to_string()),
+            Token::ILLEGAL("@".to_string()),
+            Token::INT("3".to_string()),
+            Token::SEMICOLON,
+            Token::ILLEGAL("%".to_string()),
+            Token::ILLEGAL("€".to_string()),
+            Token::EOF,
+        ];
+
+        let mut lex = Lexer::new(input);
+        for entry in options {
+            let token = lex.next_token()?;
+            println!("Expected: {entry}, got: {token}");
+            assert_eq!(entry, token);
+        }
+
+        let expected = vec![
+            ("Illegal character: @", 1, 11),
+            ("Illegal character: %", 1, 16),
+            ("Illegal character: €", 2, 1),
+        ];
+        assert_eq!(lex.diagnostics.len(), expected.len());
+        for (diagnostic, (message, line, column)) in lex.diagnostics.iter().zip(expected) {
+            println!("{}", diagnostic);
+            assert_eq!(diagnostic.message, message);
+            assert_eq!((diagnostic.span.start.line, diagnostic.span.start.column), (line, column));
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_logical_operators() -> Result<()> {
+        let input = "a && b || !c & d | e".to_string();
+
+        let options = vec![
+            Token::IDENT("a".to_string()),
+            Token::AND,
+            Token::IDENT("b".to_string()),
+            Token::OR,
+            Token::BANG,
+            Token::IDENT("c".to_string()),
+            Token::ILLEGAL("&".to_string()),
+            Token::IDENT("d".to_string()),
+            Token::ILLEGAL("|".to_string()),
+            Token::IDENT("e".to_string()),
+            Token::EOF,
+        ];
+
+        let mut lex = Lexer::new(input);
+        for entry in options {
+            let token = lex.next_token()?;
+            println!("Expected: {entry}, got: {token}");
+            assert_eq!(entry, token);
+        }
+        Ok(())
+    }
+    #[test]
+    pub fn test_float_literals() -> Result<()> {
+        let input = "3.14 1e-9 .5 2E+3 10 1.x 1e . 7".to_string();
+
+        let options = vec![
+            Token::FLOAT("3.14".to_string()),
+            Token::FLOAT("1e-9".to_string()),
+            Token::FLOAT(".5".to_string()),
+            Token::FLOAT("2E+3".to_string()),
+            Token::INT("10".to_string()),
+            Token::INT("1".to_string()),
+            Token::ILLEGAL(".".to_string()),
+            Token::IDENT("x".to_string()),
+            Token::INT("1".to_string()),
+            Token::IDENT("e".to_string()),
+            Token::ILLEGAL(".".to_string()),
+            Token::INT("7".to_string()),
+            Token::EOF,
+        ];
+
+        let mut lex = Lexer::new(input);
+        for entry in options {
+            let token = lex.next_token()?;
+            println!("Expected: {entry}, got: {token}");
+            assert_eq!(entry, token);
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_string_escapes() -> Result<()> {
+        let input = vec![
+            (r#""say \"hi\"""#, "say \"hi\""),
+            (r#""a\tb\nc\\d\r""#, "a\tb\nc\\d\r"),
+            (r#""\u{48}\u{e9}\u{1F600}""#, "Hé😀"),
+            ("\"line one\nline two\"", "line one\nline two"),
+            ("`raw \\n ${x} \"quoted\"\nnext line`", "raw \\n ${x} \"quoted\"\nnext line"),
+            ("``", ""),
+        ];
+        for (entry, expected) in input {
+            let mut lex = Lexer::new(entry.to_string());
+            assert_eq!(lex.next_token()?, Token::STRING(expected.to_string()), "input: {}", entry);
+            assert_eq!(lex.next_token()?, Token::EOF, "input: {}", entry);
+            assert!(lex.diagnostics.is_empty(), "input: {}, got: {:?}", entry, lex.diagnostics);
+        }
+
+        // whitespace after the closing quote is left alone
+        let mut lex = Lexer::new(r#""a"  b"#.to_string());
+        lex.next_token()?;
+        let spanned = lex.next_spanned_token()?;
+        assert_eq!(spanned.token, Token::IDENT("b".to_string()));
+        assert_eq!(spanned.span.start.column, 6);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_string_errors() -> Result<()> {
+        // (input, string token, message, start column, end column)
+        let input = vec![
+            (r#""a\qb""#, "ab", r"Unknown escape sequence: \q", 3, 5),
+            (r#""\u{110000}""#, "", r"Invalid unicode escape: \u{110000}", 2, 12),
+            (r#""\u{}x""#, "x", r"Invalid unicode escape: \u{}", 2, 6),
+            (r#""\u{41""#, "", "Invalid unicode escape: expected }", 2, 7),
+            (r#""\u41""#, "41", "Invalid unicode escape: expected {", 2, 4),
+            (r#""never closed"#, "never closed", "Unterminated string", 1, 14),
+            ("`never closed\n", "never closed\n", "Unterminated string", 1, 1),
+        ];
+        for (entry, expected, message, start, end) in input {
+            let mut lex = Lexer::new(entry.to_string());
+            assert_eq!(lex.next_token()?, Token::STRING(expected.to_string()), "input: {}", entry);
+            assert_eq!(lex.next_token()?, Token::EOF, "input: {}", entry);
+            assert_eq!(lex.diagnostics.len(), 1, "input: {}, got: {:?}", entry, lex.diagnostics);
+            let diagnostic = &lex.diagnostics[0];
+            assert_eq!(diagnostic.message, message, "input: {}", entry);
+            assert_eq!((diagnostic.span.start.column, diagnostic.span.end.column), (start, end), "input: {}", entry);
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_comments() -> Result<()> {
+        let input = "let a = 10 / 2; // half\n/* block /* nested */ still comment */ a //\n/**/b";
+
+        let skipped = vec![
+            Token::LET,
+            Token::IDENT("a".to_string()),
+            Token::ASSIGN,
+            Token::INT("10".to_string()),
+            Token::FSLASH,
+            Token::INT("2".to_string()),
+            Token::SEMICOLON,
+            Token::IDENT("a".to_string()),
+            Token::IDENT("b".to_string()),
+            Token::EOF,
+        ];
+        let mut lex = Lexer::new(input.to_string());
+        for entry in skipped {
+            let token = lex.next_token()?;
+            println!("Expected: {entry}, got: {token}");
+            assert_eq!(entry, token);
+        }
+
+        // kept as trivia, each with its own span
+        let kept = vec![
+            (Token::COMMENT("// half".to_string()), 1, 17),
+            (Token::COMMENT("/* block /* nested */ still comment */".to_string()), 2, 1),
+            (Token::IDENT("a".to_string()), 2, 40),
+            (Token::COMMENT("//".to_string()), 2, 42),
+            (Token::COMMENT("/**/".to_string()), 3, 1),
+            (Token::IDENT("b".to_string()), 3, 5),
+            (Token::EOF, 3, 6),
+        ];
+        let mut lex = Lexer::new(input.to_string());
+        lex.keep_comments = true;
+        for _ in 0..7 {
+            lex.next_token()?;
+        }
+        for (token, line, column) in kept {
+            let spanned = lex.next_spanned_token()?;
+            println!("Expected: {token}, got: {} at {}", spanned.token, spanned.span);
+            assert_eq!(spanned.token, token);
+            assert_eq!((spanned.span.start.line, spanned.span.start.column), (line, column));
+        }
+        assert!(lex.diagnostics.is_empty());
+
+        let mut lex = Lexer::new("1 /* open /* */ \n 2".to_string());
+        assert_eq!(lex.next_token()?, Token::INT("1".to_string()));
+        assert_eq!(lex.next_token()?, Token::EOF);
+        assert_eq!(lex.diagnostics.len(), 1);
+        assert_eq!(lex.diagnostics[0].message, "Unterminated block comment");
+        assert_eq!(lex.diagnostics[0].span.start.column, 3);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_unicode_identifiers() -> Result<()> {
+        let input = "let café = \"naïve\"; 名前 + _x1 € é2\n  ü".to_string();
+
+        // (token, line, column, byte offset)
+        let options = vec![
+            (Token::LET, 1, 1, 0),
+            (Token::IDENT("café".to_string()), 1, 5, 4),
+            (Token::ASSIGN, 1, 10, 10),
+            (Token::STRING("naïve".to_string()), 1, 12, 12),
+            (Token::SEMICOLON, 1, 19, 20),
+            (Token::IDENT("名前".to_string()), 1, 21, 22),
+            (Token::PLUS, 1, 24, 29),
+            (Token::IDENT("_x1".to_string()), 1, 26, 31),
+            (Token::ILLEGAL("€".to_string()), 1, 30, 35),
+            (Token::IDENT("é2".to_string()), 1, 32, 39),
+            (Token::IDENT("ü".to_string()), 2, 3, 45),
+            (Token::EOF, 2, 4, 47),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for (token, line, column, offset) in options {
+            let spanned = lex.next_spanned_token()?;
+            println!("Expected: {token}, got: {} at {}", spanned.token, spanned.span);
+            assert_eq!(spanned.token, token);
+            assert_eq!(spanned.span.start, Position { line, column, offset });
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_string_interpolation() -> Result<()> {
+        let input = r#""Hello ${name}, you have ${count + 1} items" "${ {"a": 1}["a"] }${f("in ${x}")}" "\${not} $5""#;
+
+        let options = vec![
+            Token::INTERPSTART("Hello ".to_string()),
+            Token::IDENT("name".to_string()),
+            Token::INTERPMID(", you have ".to_string()),
+            Token::IDENT("count".to_string()),
+            Token::PLUS,
+            Token::INT("1".to_string()),
+            Token::INTERPEND(" items".to_string()),
+            // braces inside the ${ } are ordinary tokens
+            Token::INTERPSTART("".to_string()),
+            Token::LBRACE,
+            Token::STRING("a".to_string()),
+            Token::COLON,
+            Token::INT("1".to_string()),
+            Token::RBRACE,
+            Token::LBRACKET,
+            Token::STRING("a".to_string()),
+            Token::RBRACKET,
+            Token::INTERPMID("".to_string()),
+            Token::IDENT("f".to_string()),
+            Token::LPAREN,
+            Token::INTERPSTART("in ".to_string()),
+            Token::IDENT("x".to_string()),
+            Token::INTERPEND("".to_string()),
+            Token::RPAREN,
+            Token::INTERPEND("".to_string()),
+            Token::STRING("${not} $5".to_string()),
+            Token::EOF,
+        ];
+
+        let mut lex = Lexer::new(input.to_string());
+        for entry in options {
+            let token = lex.next_token()?;
+            println!("Expected: {entry}, got: {token}");
+            assert_eq!(entry, token);
+        }
+        assert!(lex.diagnostics.is_empty(), "got: {:?}", lex.diagnostics);
+
+        // left open inside the ${ or after it, both point back at the opening quote
+        for entry in [r#"x = "a ${b"#, r#"x = "a ${b} c"#] {
+            let mut lex = Lexer::new(entry.to_string());
+            while lex.next_token()? != Token::EOF {}
+            assert_eq!(lex.diagnostics.len(), 1, "input: {}", entry);
+            assert_eq!(lex.diagnostics[0].message, "Unterminated string");
+            assert_eq!(lex.diagnostics[0].span.start.column, 5);
+        }
+        Ok(())
+    }
 }